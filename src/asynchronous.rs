@@ -0,0 +1,57 @@
+//! Asynchronous ownership operations running the blocking system calls on the `tokio` blocking thread pool.
+use crate::{FileOwnerError, Group, Owner};
+use std::convert::TryInto;
+use std::io;
+use std::path::Path;
+use tokio::task::spawn_blocking;
+
+async fn run_blocking<T: Send + 'static>(f: impl FnOnce() -> Result<T, FileOwnerError> + Send + 'static) -> Result<T, FileOwnerError> {
+    spawn_blocking(f).await.map_err(io::Error::other)?
+}
+
+/// Sets owner to file at the given path without blocking the runtime.
+///
+/// The owner is resolved on the calling task; only the `chown` call is run with `spawn_blocking`.
+pub async fn set_owner_async<E: Into<FileOwnerError>>(path: impl AsRef<Path>, owner: impl TryInto<Owner, Error = E>) -> Result<(), FileOwnerError> {
+    let owner = owner.try_into().map_err(Into::into)?;
+    let path = path.as_ref().to_owned();
+    run_blocking(move || crate::set_owner(path, owner)).await
+}
+
+/// Sets group to file at the given path without blocking the runtime.
+///
+/// The group is resolved on the calling task; only the `chown` call is run with `spawn_blocking`.
+pub async fn set_group_async<E: Into<FileOwnerError>>(path: impl AsRef<Path>, group: impl TryInto<Group, Error = E>) -> Result<(), FileOwnerError> {
+    let group = group.try_into().map_err(Into::into)?;
+    let path = path.as_ref().to_owned();
+    run_blocking(move || crate::set_group(path, group)).await
+}
+
+/// Gets owner of a file at the given path without blocking the runtime.
+pub async fn owner_async(path: impl AsRef<Path>) -> Result<Owner, FileOwnerError> {
+    let path = path.as_ref().to_owned();
+    run_blocking(move || crate::owner(path)).await
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_owner_async() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        assert_eq!(owner_async(file.path()).await.unwrap(), crate::owner(file.path()).unwrap());
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_set_get_async() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+
+        set_owner_async(file.path(), "nobody").await.unwrap();
+        set_group_async(file.path(), "nogroup").await.unwrap();
+
+        assert_eq!(owner_async(file.path()).await.unwrap().name().unwrap().as_deref(), Some("nobody"));
+        assert_eq!(crate::group(file.path()).unwrap().name().unwrap().as_deref(), Some("nogroup"));
+    }
+}
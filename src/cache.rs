@@ -0,0 +1,221 @@
+//! Memoized user and group name resolution.
+use crate::{FileOwnerError, Group, Owner, OwnerGroupSpec};
+use crate::parse::{parse_group, parse_owner, parse_spec};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+#[cfg(test)]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Caches user name to UID and UID to user name lookups.
+///
+/// Lookups go to the user database only the first time a given name or UID is resolved successfully.
+/// UIDs without a user, such as those of deleted accounts, are cached too so repeated [`name_of`](UserCache::name_of) calls for them are cheap.
+/// The cache uses interior `Mutex`es so it can be shared by reference, including across threads.
+/// Entries are never invalidated, so changes to the user database are not observed by an existing cache.
+#[derive(Debug, Default)]
+pub struct UserCache {
+    by_name: Mutex<HashMap<String, Owner>>,
+    names: Mutex<HashMap<Owner, Option<String>>>,
+    #[cfg(test)]
+    lookups: AtomicUsize,
+}
+
+impl UserCache {
+    /// Constructs an empty cache.
+    pub fn new() -> UserCache {
+        UserCache::default()
+    }
+
+    /// Gets Owner by user name, using the cached value if present.
+    pub fn owner_from_name(&self, name: &str) -> Result<Owner, FileOwnerError> {
+        if let Some(owner) = lock(&self.by_name).get(name) {
+            return Ok(*owner);
+        }
+        #[cfg(test)]
+        self.lookups.fetch_add(1, Ordering::Relaxed);
+        let owner = Owner::from_name(name)?;
+        lock(&self.by_name).insert(name.to_owned(), owner);
+        Ok(owner)
+    }
+
+    /// Gets user name of the given Owner, using the cached value if present.
+    ///
+    /// A `None` result for a UID without a user is cached as well.
+    pub fn name_of(&self, owner: Owner) -> Result<Option<String>, FileOwnerError> {
+        if let Some(name) = lock(&self.names).get(&owner) {
+            return Ok(name.clone());
+        }
+        #[cfg(test)]
+        self.lookups.fetch_add(1, Ordering::Relaxed);
+        let name = owner.name()?;
+        lock(&self.names).insert(owner, name.clone());
+        Ok(name)
+    }
+}
+
+/// Caches group name to GID and GID to group name lookups.
+///
+/// Behaves like [`UserCache`] for groups, including caching GIDs without a group, and can likewise be shared by reference across a whole batch of operations.
+#[derive(Debug, Default)]
+pub struct GroupCache {
+    by_name: Mutex<HashMap<String, Group>>,
+    names: Mutex<HashMap<Group, Option<String>>>,
+    #[cfg(test)]
+    lookups: AtomicUsize,
+}
+
+impl GroupCache {
+    /// Constructs an empty cache.
+    pub fn new() -> GroupCache {
+        GroupCache::default()
+    }
+
+    /// Gets Group by group name, using the cached value if present.
+    pub fn group_from_name(&self, name: &str) -> Result<Group, FileOwnerError> {
+        if let Some(group) = lock(&self.by_name).get(name) {
+            return Ok(*group);
+        }
+        #[cfg(test)]
+        self.lookups.fetch_add(1, Ordering::Relaxed);
+        let group = Group::from_name(name)?;
+        lock(&self.by_name).insert(name.to_owned(), group);
+        Ok(group)
+    }
+
+    /// Gets group name of the given Group, using the cached value if present.
+    ///
+    /// A `None` result for a GID without a group is cached as well.
+    pub fn name_of(&self, group: Group) -> Result<Option<String>, FileOwnerError> {
+        if let Some(name) = lock(&self.names).get(&group) {
+            return Ok(name.clone());
+        }
+        #[cfg(test)]
+        self.lookups.fetch_add(1, Ordering::Relaxed);
+        let name = group.name()?;
+        lock(&self.names).insert(group, name.clone());
+        Ok(name)
+    }
+}
+
+/// Resolves owners, groups and owner/group specifications through shared user and group caches.
+///
+/// Accepts the same syntax as the `FromStr` implementations of [`Owner`], [`Group`] and [`OwnerGroupSpec`].
+/// Numeric ids are used as is without any lookup. The login group for `"user:"` specifications is not cached.
+/// Resolved values can then be passed to any function of this crate, such as [`set_owner_recursive`](crate::set_owner_recursive).
+#[derive(Debug, Default)]
+pub struct Resolver {
+    users: UserCache,
+    groups: GroupCache,
+}
+
+impl Resolver {
+    /// Constructs a resolver with empty caches.
+    pub fn new() -> Resolver {
+        Resolver::default()
+    }
+
+    /// Gets the user cache.
+    pub fn users(&self) -> &UserCache {
+        &self.users
+    }
+
+    /// Gets the group cache.
+    pub fn groups(&self) -> &GroupCache {
+        &self.groups
+    }
+
+    /// Resolves a user name or numeric UID to Owner.
+    pub fn resolve_owner(&self, s: &str) -> Result<Owner, FileOwnerError> {
+        parse_owner(s, |name| self.users.owner_from_name(name))
+    }
+
+    /// Resolves a group name or numeric GID to Group.
+    pub fn resolve_group(&self, s: &str) -> Result<Group, FileOwnerError> {
+        parse_group(s, |name| self.groups.group_from_name(name))
+    }
+
+    /// Resolves a `chown` style owner/group specification.
+    pub fn resolve_spec(&self, s: &str) -> Result<OwnerGroupSpec, FileOwnerError> {
+        parse_spec(s, |name| self.users.owner_from_name(name), |name| self.groups.group_from_name(name))
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_cache() {
+        let cache = UserCache::new();
+        let nobody = cache.owner_from_name("nobody").unwrap();
+        assert_eq!(nobody, Owner::from_name("nobody").unwrap());
+        assert_eq!(cache.owner_from_name("nobody").unwrap(), nobody);
+        assert_eq!(cache.lookups.load(Ordering::Relaxed), 1);
+
+        assert_eq!(cache.name_of(nobody).unwrap().as_deref(), Some("nobody"));
+        assert_eq!(cache.name_of(nobody).unwrap().as_deref(), Some("nobody"));
+        assert_eq!(cache.lookups.load(Ordering::Relaxed), 2);
+
+        assert!(matches!(cache.owner_from_name("nonexistent-user"), Err(FileOwnerError::UserNotFound(_))));
+        assert_eq!(cache.name_of(Owner::from_uid(321321)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_group_cache() {
+        let cache = GroupCache::new();
+        let nogroup = cache.group_from_name("nogroup").unwrap();
+        assert_eq!(nogroup, Group::from_name("nogroup").unwrap());
+        assert_eq!(cache.name_of(nogroup).unwrap().as_deref(), Some("nogroup"));
+        assert_eq!(cache.group_from_name(&cache.name_of(nogroup).unwrap().unwrap()).unwrap(), nogroup);
+        assert_eq!(cache.lookups.load(Ordering::Relaxed), 2);
+
+        assert!(matches!(cache.group_from_name("nonexistent-group"), Err(FileOwnerError::GroupNotFound(_))));
+        assert!(matches!(cache.group_from_name("nonexistent-group"), Err(FileOwnerError::GroupNotFound(_))));
+        assert_eq!(cache.name_of(Group::from_gid(321321)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_negative_cache() {
+        let users = UserCache::new();
+        for _ in 0..3 {
+            assert_eq!(users.name_of(Owner::from_uid(321321)).unwrap(), None);
+        }
+        assert_eq!(users.lookups.load(Ordering::Relaxed), 1);
+
+        let groups = GroupCache::new();
+        for _ in 0..3 {
+            assert_eq!(groups.name_of(Group::from_gid(321321)).unwrap(), None);
+        }
+        assert_eq!(groups.lookups.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_resolver() {
+        let resolver = Resolver::new();
+        let nobody = Owner::from_name("nobody").unwrap();
+        let nogroup = Group::from_name("nogroup").unwrap();
+
+        for _ in 0..3 {
+            assert_eq!(resolver.resolve_owner("nobody").unwrap(), nobody);
+            assert_eq!(resolver.resolve_group("nogroup").unwrap(), nogroup);
+            assert_eq!(resolver.resolve_spec("nobody:nogroup").unwrap(), OwnerGroupSpec { owner: Some(nobody), group: Some(nogroup) });
+        }
+        assert_eq!(resolver.users().lookups.load(Ordering::Relaxed), 1);
+        assert_eq!(resolver.groups().lookups.load(Ordering::Relaxed), 1);
+
+        assert_eq!(resolver.resolve_owner("321321").unwrap(), Owner::from_uid(321321));
+        assert_eq!(resolver.resolve_spec(":321321").unwrap(), OwnerGroupSpec { owner: None, group: Some(Group::from_gid(321321)) });
+        assert_eq!(resolver.resolve_spec("nobody").unwrap(), OwnerGroupSpec { owner: Some(nobody), group: None });
+        assert_eq!(resolver.resolve_spec("").unwrap(), OwnerGroupSpec::default());
+        assert_eq!(resolver.users().lookups.load(Ordering::Relaxed), 1);
+        assert_eq!(resolver.groups().lookups.load(Ordering::Relaxed), 1);
+
+        assert_eq!(resolver.resolve_spec("root:").unwrap().group, Some(Owner::from_uid(0).primary_group().unwrap()));
+        assert!(matches!(resolver.resolve_spec("nobody:nonexistent-group"), Err(FileOwnerError::GroupNotFound(_))));
+    }
+}
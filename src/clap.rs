@@ -0,0 +1,58 @@
+//! Clap argument parsing support for ownership types.
+use crate::{Group, Owner, OwnerGroupSpec};
+use ::clap::builder::{ValueParser, ValueParserFactory};
+
+/// Parses user name or UID arguments with `value_parser!(Owner)`.
+impl ValueParserFactory for Owner {
+    type Parser = ValueParser;
+
+    fn value_parser() -> ValueParser {
+        ValueParser::new(|s: &str| s.parse::<Owner>())
+    }
+}
+
+/// Parses group name or GID arguments with `value_parser!(Group)`.
+impl ValueParserFactory for Group {
+    type Parser = ValueParser;
+
+    fn value_parser() -> ValueParser {
+        ValueParser::new(|s: &str| s.parse::<Group>())
+    }
+}
+
+/// Parses `user:group` arguments with `value_parser!(OwnerGroupSpec)`.
+impl ValueParserFactory for OwnerGroupSpec {
+    type Parser = ValueParser;
+
+    fn value_parser() -> ValueParser {
+        ValueParser::new(|s: &str| s.parse::<OwnerGroupSpec>())
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use ::clap::{value_parser, Arg, Command};
+
+    fn command() -> Command {
+        Command::new("test")
+            .arg(Arg::new("owner").long("owner").value_parser(value_parser!(Owner)))
+            .arg(Arg::new("group").long("group").value_parser(value_parser!(Group)))
+            .arg(Arg::new("spec").long("spec").value_parser(value_parser!(OwnerGroupSpec)))
+    }
+
+    #[test]
+    fn test_parse() {
+        let matches = command().try_get_matches_from(["test", "--owner", "nobody", "--group", "321321", "--spec", "nobody:nogroup"]).unwrap();
+        assert_eq!(matches.get_one::<Owner>("owner"), Some(&Owner::from_name("nobody").unwrap()));
+        assert_eq!(matches.get_one::<Group>("group"), Some(&Group::from_gid(321321)));
+        assert_eq!(matches.get_one::<OwnerGroupSpec>("spec"), Some(&"nobody:nogroup".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let err = command().try_get_matches_from(["test", "--owner", "nonexistent-user"]).unwrap_err();
+        assert_eq!(err.kind(), ::clap::error::ErrorKind::ValueValidation);
+        assert!(err.to_string().contains("user name \"nonexistent-user\" not found"), "{}", err);
+    }
+}
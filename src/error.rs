@@ -0,0 +1,192 @@
+//! Error type shared by the Unix implementation and the non-Unix stub.
+use crate::Ownership;
+#[cfg(unix)]
+use nix::errno::Errno;
+use std::convert::Infallible;
+#[cfg(unix)]
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// File owner or group error.
+///
+/// A failed `chown` of a path is reported as [`FileOwnerError::PermissionDenied`] for `EPERM`/`EACCES`, [`FileOwnerError::PathNotFound`] for `ENOENT`
+/// and `NixError` for any other errno; functions taking a file descriptor report every errno as `NixError`.
+///
+/// A name missing from the user or group database is reported as [`FileOwnerError::UserNotFound`] or [`FileOwnerError::GroupNotFound`],
+/// while a UID or GID that a lookup requires but has no entry is reported as [`FileOwnerError::UidNotFound`] or [`FileOwnerError::GidNotFound`].
+#[derive(Debug)]
+pub enum FileOwnerError {
+    IoError(io::Error),
+    Io { path: PathBuf, source: io::Error },
+    #[cfg(unix)]
+    NixError(nix::Error),
+    UserNotFound(String),
+    GroupNotFound(String),
+    UidNotFound(u32),
+    GidNotFound(u32),
+    EntryError(PathBuf, Box<FileOwnerError>),
+    PermissionDenied { path: PathBuf },
+    PathNotFound(PathBuf),
+    IdOutOfRange(i64),
+    InvalidPattern { pattern: String, message: String },
+    RolledBack { original: Ownership, source: Box<FileOwnerError> },
+    InvalidName { name: String, reason: &'static str },
+    UnmappedId(u32),
+    /// The operation is not available on this platform, e.g. any file operation of the non-Unix stub.
+    Unsupported,
+}
+
+impl FileOwnerError {
+    /// Gets the underlying OS error number if any.
+    ///
+    /// [`FileOwnerError::PermissionDenied`] is reported as `EPERM` and [`FileOwnerError::PathNotFound`] as `ENOENT`.
+    /// For [`FileOwnerError::EntryError`] and [`FileOwnerError::RolledBack`] the errno of the wrapped error is returned.
+    ///
+    /// Available on Unix only.
+    #[cfg(unix)]
+    pub fn errno(&self) -> Option<Errno> {
+        match self {
+            FileOwnerError::IoError(err) | FileOwnerError::Io { source: err, .. } => err.raw_os_error().and_then(|errno| Errno::try_from(io::Error::from_raw_os_error(errno)).ok()),
+            FileOwnerError::NixError(err) => Some(*err),
+            FileOwnerError::EntryError(_, err) | FileOwnerError::RolledBack { source: err, .. } => err.errno(),
+            FileOwnerError::PermissionDenied { .. } => Some(Errno::EPERM),
+            FileOwnerError::PathNotFound(_) => Some(Errno::ENOENT),
+            FileOwnerError::UserNotFound(_) | FileOwnerError::GroupNotFound(_) | FileOwnerError::UidNotFound(_) | FileOwnerError::GidNotFound(_) | FileOwnerError::IdOutOfRange(_) | FileOwnerError::InvalidPattern { .. } | FileOwnerError::InvalidName { .. } | FileOwnerError::UnmappedId(_) | FileOwnerError::Unsupported => None,
+        }
+    }
+
+    /// Attaches the given path to an error that does not carry one.
+    ///
+    /// [`FileOwnerError::IoError`] becomes [`FileOwnerError::PathNotFound`] if the file was not found or [`FileOwnerError::Io`] otherwise.
+    /// Errors that already carry a path are returned unchanged and any other error is wrapped in [`FileOwnerError::EntryError`].
+    pub fn with_path(self, path: impl AsRef<Path>) -> FileOwnerError {
+        let path = path.as_ref();
+        match self {
+            FileOwnerError::IoError(err) => path_io_error(path, err),
+            #[cfg(unix)]
+            FileOwnerError::NixError(Errno::ENOENT) => FileOwnerError::PathNotFound(path.to_owned()),
+            err @ (FileOwnerError::Io { .. } | FileOwnerError::EntryError(..) | FileOwnerError::PermissionDenied { .. } | FileOwnerError::PathNotFound(_)) => err,
+            err => FileOwnerError::EntryError(path.to_owned(), Box::new(err)),
+        }
+    }
+
+    fn io_kind(&self) -> io::ErrorKind {
+        match self {
+            FileOwnerError::IoError(err) | FileOwnerError::Io { source: err, .. } => err.kind(),
+            #[cfg(unix)]
+            FileOwnerError::NixError(errno) => io::Error::from_raw_os_error(*errno as i32).kind(),
+            FileOwnerError::EntryError(_, err) | FileOwnerError::RolledBack { source: err, .. } => err.io_kind(),
+            FileOwnerError::PermissionDenied { .. } => io::ErrorKind::PermissionDenied,
+            FileOwnerError::PathNotFound(_) | FileOwnerError::UserNotFound(_) | FileOwnerError::GroupNotFound(_) | FileOwnerError::UidNotFound(_) | FileOwnerError::GidNotFound(_) => io::ErrorKind::NotFound,
+            FileOwnerError::IdOutOfRange(_) | FileOwnerError::InvalidPattern { .. } | FileOwnerError::InvalidName { .. } | FileOwnerError::UnmappedId(_) => io::ErrorKind::InvalidInput,
+            FileOwnerError::Unsupported => io::ErrorKind::Unsupported,
+        }
+    }
+}
+
+impl Display for FileOwnerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileOwnerError::IoError(err) => write!(f, "I/O error: {}", err),
+            FileOwnerError::Io { path, source } => write!(f, "I/O error on {:?}: {}", path, source),
+            #[cfg(unix)]
+            FileOwnerError::NixError(err) => write!(f, "*nix error: {}", err),
+            FileOwnerError::UserNotFound(name) => write!(f, "user name {:?} not found", name),
+			FileOwnerError::GroupNotFound(name) => write!(f, "group name {:?} not found", name),
+            FileOwnerError::UidNotFound(uid) => write!(f, "no user with UID {}", uid),
+            FileOwnerError::GidNotFound(gid) => write!(f, "no group with GID {}", gid),
+            FileOwnerError::EntryError(path, err) => write!(f, "error processing {:?}: {}", path, err),
+            FileOwnerError::PermissionDenied { path } => write!(f, "permission denied changing ownership of {:?}", path),
+            FileOwnerError::PathNotFound(path) => write!(f, "path {:?} not found", path),
+            FileOwnerError::IdOutOfRange(id) => write!(f, "id {} out of u32 range", id),
+            FileOwnerError::InvalidPattern { pattern, message } => write!(f, "invalid pattern {:?}: {}", pattern, message),
+            FileOwnerError::RolledBack { original, source } => write!(f, "{} (owner restored to {:#})", source, original.owner),
+            FileOwnerError::InvalidName { name, reason } => write!(f, "invalid name {:?}: {}", name, reason),
+            FileOwnerError::UnmappedId(id) => write!(f, "id {} has no mapping", id),
+            FileOwnerError::Unsupported => write!(f, "operation not supported on this platform"),
+        }
+    }
+}
+
+impl Error for FileOwnerError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FileOwnerError::IoError(err) => Some(err),
+            FileOwnerError::Io { source, .. } => Some(source),
+            #[cfg(unix)]
+            FileOwnerError::NixError(err) => Some(err),
+            FileOwnerError::UserNotFound(_) => None,
+			FileOwnerError::GroupNotFound(_) => None,
+            FileOwnerError::UidNotFound(_) => None,
+            FileOwnerError::GidNotFound(_) => None,
+            FileOwnerError::EntryError(_, err) => Some(err.as_ref()),
+            FileOwnerError::RolledBack { source, .. } => Some(source.as_ref()),
+            FileOwnerError::PermissionDenied { .. } => None,
+            FileOwnerError::PathNotFound(_) => None,
+            FileOwnerError::IdOutOfRange(_) => None,
+            FileOwnerError::InvalidPattern { .. } => None,
+            FileOwnerError::InvalidName { .. } => None,
+            FileOwnerError::UnmappedId(_) => None,
+            FileOwnerError::Unsupported => None,
+        }
+    }
+}
+
+impl From<io::Error> for FileOwnerError {
+    fn from(err: io::Error) -> FileOwnerError {
+        FileOwnerError::IoError(err)
+    }
+}
+
+#[cfg(unix)]
+impl From<nix::Error> for FileOwnerError {
+    fn from(err: nix::Error) -> FileOwnerError {
+        FileOwnerError::NixError(err)
+    }
+}
+
+impl From<Infallible> for FileOwnerError {
+    fn from(_err: Infallible) -> FileOwnerError {
+        unreachable!()
+    }
+}
+
+/// Converts to `io::Error` with the `io::ErrorKind` matching the underlying errno.
+///
+/// [`FileOwnerError::IoError`] is unwrapped and [`FileOwnerError::NixError`] becomes a plain OS error.
+/// Other variants are kept as the inner error so their message, including any path, is preserved.
+impl From<FileOwnerError> for io::Error {
+    fn from(err: FileOwnerError) -> io::Error {
+        match err {
+            FileOwnerError::IoError(err) => err,
+            #[cfg(unix)]
+            FileOwnerError::NixError(errno) => io::Error::from_raw_os_error(errno as i32),
+            err => io::Error::new(err.io_kind(), err),
+        }
+    }
+}
+
+pub(crate) fn path_io_error(path: &Path, source: io::Error) -> FileOwnerError {
+    match source.kind() {
+        io::ErrorKind::NotFound => FileOwnerError::PathNotFound(path.to_owned()),
+        _ => FileOwnerError::Io { path: path.to_owned(), source },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unsupported() {
+        let err = FileOwnerError::Unsupported;
+        assert_eq!(err.to_string(), "operation not supported on this platform");
+        assert!(err.source().is_none());
+        #[cfg(unix)]
+        assert_eq!(err.errno(), None);
+        assert_eq!(io::Error::from(err).kind(), io::ErrorKind::Unsupported);
+    }
+}
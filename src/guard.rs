@@ -0,0 +1,110 @@
+//! Scoped ownership changes restored on drop.
+use crate::{ownership, set_ownership, FileOwnerError, Ownership};
+use std::path::{Path, PathBuf};
+
+/// Records the ownership of a file and restores it when dropped.
+///
+/// Restoring on drop cannot report failures, so any error (such as the file having been removed) is silently ignored.
+/// Use [`OwnershipGuard::restore`] to observe the result, or [`OwnershipGuard::disarm`] to keep the current ownership.
+#[derive(Debug)]
+#[must_use = "ownership is restored as soon as the guard is dropped"]
+pub struct OwnershipGuard {
+    path: PathBuf,
+    ownership: Ownership,
+    armed: bool,
+}
+
+impl OwnershipGuard {
+    /// Records the current ownership of the file at the given path, following symlinks.
+    pub fn take(path: impl AsRef<Path>) -> Result<OwnershipGuard, FileOwnerError> {
+        let path = path.as_ref();
+        Ok(OwnershipGuard { path: path.to_owned(), ownership: ownership(path)?, armed: true })
+    }
+
+    /// Gets the path of the guarded file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Gets the recorded ownership that will be restored.
+    pub fn ownership(&self) -> Ownership {
+        self.ownership
+    }
+
+    /// Restores the recorded ownership now and reports any failure.
+    pub fn restore(mut self) -> Result<(), FileOwnerError> {
+        self.armed = false;
+        set_ownership(&self.path, &self.ownership)
+    }
+
+    /// Keeps the current ownership and does not restore anything on drop.
+    pub fn disarm(mut self) {
+        self.armed = false;
+    }
+}
+
+impl Drop for OwnershipGuard {
+    fn drop(&mut self) {
+        if self.armed {
+            let _ = set_ownership(&self.path, &self.ownership);
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::{owner_group, set_owner_group, Group, Owner};
+
+    #[test]
+    fn test_take_missing() {
+        assert!(matches!(OwnershipGuard::take("/nonexistent/file-owner"), Err(FileOwnerError::PathNotFound(_))));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_drop_restores() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let before = owner_group(file.path()).unwrap();
+        {
+            let guard = OwnershipGuard::take(file.path()).unwrap();
+            assert_eq!(guard.ownership(), before.into());
+            set_owner_group(file.path(), "nobody", "nogroup").unwrap();
+        }
+        assert_eq!(owner_group(file.path()).unwrap(), before);
+
+        let result = std::panic::catch_unwind(|| {
+            let _guard = OwnershipGuard::take(file.path()).unwrap();
+            set_owner_group(file.path(), "nobody", "nogroup").unwrap();
+            panic!("early exit");
+        });
+        assert!(result.is_err());
+        assert_eq!(owner_group(file.path()).unwrap(), before);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_disarm() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let guard = OwnershipGuard::take(file.path()).unwrap();
+        set_owner_group(file.path(), "nobody", "nogroup").unwrap();
+        guard.disarm();
+        assert_eq!(owner_group(file.path()).unwrap(), (Owner::from_name("nobody").unwrap(), Group::from_name("nogroup").unwrap()));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_restore() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let before = owner_group(file.path()).unwrap();
+        let guard = OwnershipGuard::take(file.path()).unwrap();
+        set_owner_group(file.path(), "nobody", "nogroup").unwrap();
+        guard.restore().unwrap();
+        assert_eq!(owner_group(file.path()).unwrap(), before);
+
+        let guard = OwnershipGuard::take(file.path()).unwrap();
+        let path = file.path().to_owned();
+        file.close().unwrap();
+        assert!(matches!(guard.restore(), Err(FileOwnerError::PathNotFound(p)) if p == path));
+    }
+}
@@ -0,0 +1,165 @@
+//! Translation of numeric ids stored in archives to ids of the host.
+use crate::{set_group, set_owner, FileOwnerError, Group, Owner};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Translation table from UIDs stored in an archive to owners on the host.
+///
+/// UIDs without an entry are kept as is with [`UidMap::new`] (like `tar --numeric-owner`) or rejected with [`UidMap::strict`].
+#[derive(Debug, Clone)]
+pub struct UidMap {
+    map: HashMap<u32, Owner>,
+    identity: bool,
+}
+
+impl UidMap {
+    /// Constructs an empty map that keeps unmapped UIDs unchanged.
+    pub fn new() -> UidMap {
+        UidMap { map: HashMap::new(), identity: true }
+    }
+
+    /// Constructs an empty map that rejects unmapped UIDs with [`FileOwnerError::UnmappedId`].
+    pub fn strict() -> UidMap {
+        UidMap { map: HashMap::new(), identity: false }
+    }
+
+    /// Maps UID stored in the archive to the given Owner.
+    pub fn insert(&mut self, archive_uid: u32, owner: Owner) {
+        self.map.insert(archive_uid, owner);
+    }
+
+    /// Translates UID stored in the archive to Owner on the host.
+    pub fn map(&self, archive_uid: u32) -> Result<Owner, FileOwnerError> {
+        match self.map.get(&archive_uid) {
+            Some(owner) => Ok(*owner),
+            None if self.identity => Ok(Owner::from_uid(archive_uid)),
+            None => Err(FileOwnerError::UnmappedId(archive_uid)),
+        }
+    }
+}
+
+impl Default for UidMap {
+    fn default() -> UidMap {
+        UidMap::new()
+    }
+}
+
+/// Translation table from GIDs stored in an archive to groups on the host.
+///
+/// Behaves like [`UidMap`] for groups.
+#[derive(Debug, Clone)]
+pub struct GidMap {
+    map: HashMap<u32, Group>,
+    identity: bool,
+}
+
+impl GidMap {
+    /// Constructs an empty map that keeps unmapped GIDs unchanged.
+    pub fn new() -> GidMap {
+        GidMap { map: HashMap::new(), identity: true }
+    }
+
+    /// Constructs an empty map that rejects unmapped GIDs with [`FileOwnerError::UnmappedId`].
+    pub fn strict() -> GidMap {
+        GidMap { map: HashMap::new(), identity: false }
+    }
+
+    /// Maps GID stored in the archive to the given Group.
+    pub fn insert(&mut self, archive_gid: u32, group: Group) {
+        self.map.insert(archive_gid, group);
+    }
+
+    /// Translates GID stored in the archive to Group on the host.
+    pub fn map(&self, archive_gid: u32) -> Result<Group, FileOwnerError> {
+        match self.map.get(&archive_gid) {
+            Some(group) => Ok(*group),
+            None if self.identity => Ok(Group::from_gid(archive_gid)),
+            None => Err(FileOwnerError::UnmappedId(archive_gid)),
+        }
+    }
+}
+
+impl Default for GidMap {
+    fn default() -> GidMap {
+        GidMap::new()
+    }
+}
+
+/// Sets owner to file at the given path translating the UID stored in an archive through the map.
+///
+/// The file is not changed if the UID has no mapping in a strict map.
+pub fn set_owner_mapped(path: impl AsRef<Path>, archive_uid: u32, map: &UidMap) -> Result<(), FileOwnerError> {
+    set_owner(path, map.map(archive_uid)?)
+}
+
+/// Sets group to file at the given path translating the GID stored in an archive through the map.
+///
+/// The file is not changed if the GID has no mapping in a strict map.
+pub fn set_group_mapped(path: impl AsRef<Path>, archive_gid: u32, map: &GidMap) -> Result<(), FileOwnerError> {
+    set_group(path, map.map(archive_gid)?)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::{current_group, current_owner, group, owner};
+
+    #[test]
+    fn test_uid_map() {
+        let mut map = UidMap::new();
+        map.insert(1000, Owner::from_uid(2000));
+        assert_eq!(map.map(1000).unwrap(), Owner::from_uid(2000));
+        assert_eq!(map.map(1001).unwrap(), Owner::from_uid(1001));
+
+        let mut strict = UidMap::strict();
+        strict.insert(1000, Owner::from_uid(2000));
+        assert_eq!(strict.map(1000).unwrap(), Owner::from_uid(2000));
+        assert!(matches!(strict.map(1001), Err(FileOwnerError::UnmappedId(1001))));
+        assert_eq!(FileOwnerError::UnmappedId(1001).to_string(), "id 1001 has no mapping");
+    }
+
+    #[test]
+    fn test_gid_map() {
+        let mut map = GidMap::default();
+        map.insert(100, Group::from_gid(50));
+        assert_eq!(map.map(100).unwrap(), Group::from_gid(50));
+        assert_eq!(map.map(101).unwrap(), Group::from_gid(101));
+        assert!(matches!(GidMap::strict().map(100), Err(FileOwnerError::UnmappedId(100))));
+    }
+
+    #[test]
+    fn test_set_mapped_own() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let mut uids = UidMap::strict();
+        uids.insert(4242, current_owner());
+        let mut gids = GidMap::strict();
+        gids.insert(4343, current_group());
+
+        set_owner_mapped(file.path(), 4242, &uids).unwrap();
+        set_group_mapped(file.path(), 4343, &gids).unwrap();
+        assert_eq!(owner(file.path()).unwrap(), current_owner());
+        assert!(matches!(set_owner_mapped(file.path(), 0, &uids), Err(FileOwnerError::UnmappedId(0))));
+        assert!(matches!(set_group_mapped(file.path(), 0, &gids), Err(FileOwnerError::UnmappedId(0))));
+        assert_eq!(group(file.path()).unwrap(), current_group());
+    }
+
+    #[test]
+    #[ignore]
+    fn test_set_owner_mapped() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let nobody = Owner::from_name("nobody").unwrap();
+        let mut map = UidMap::new();
+        map.insert(4242, nobody);
+
+        set_owner_mapped(file.path(), 4242, &map).unwrap();
+        assert_eq!(owner(file.path()).unwrap(), nobody);
+
+        set_owner_mapped(file.path(), 321321, &map).unwrap();
+        assert_eq!(owner(file.path()).unwrap(), Owner::from_uid(321321));
+
+        let mut gids = GidMap::new();
+        gids.insert(4343, Group::from_name("nogroup").unwrap());
+        set_group_mapped(file.path(), 4343, &gids).unwrap();
+        assert_eq!(group(file.path()).unwrap(), Group::from_name("nogroup").unwrap());
+    }
+}
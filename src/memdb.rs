@@ -0,0 +1,195 @@
+//! In-memory user and group databases.
+use crate::{FileOwnerError, Group, Owner};
+use std::collections::HashMap;
+
+/// Parses `passwd(5)` or `group(5)` formatted content into name and id pairs.
+///
+/// Both formats have the name in the first and the id in the third field.
+/// Empty lines, comments and lines without a numeric id are skipped.
+pub(crate) fn parse_entries(content: &str) -> impl Iterator<Item = (&str, u32)> {
+    content.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next().filter(|name| !name.is_empty())?;
+            let id = fields.nth(1)?.parse().ok()?;
+            Some((name, id))
+        })
+}
+
+/// User name to UID mapping independent of the system user database.
+///
+/// Can be parsed from `passwd(5)` formatted content, e.g. of a system image being built, or built programmatically.
+/// Lookups never make any system calls.
+#[derive(Debug, Clone, Default)]
+pub struct UserDb {
+    by_name: HashMap<String, Owner>,
+    names: HashMap<Owner, String>,
+}
+
+impl UserDb {
+    /// Constructs an empty database.
+    pub fn new() -> UserDb {
+        UserDb::default()
+    }
+
+    /// Parses `passwd(5)` formatted content.
+    ///
+    /// Empty lines and comments starting with `#` are ignored, as are malformed lines.
+    pub fn parse(content: &str) -> UserDb {
+        let mut db = UserDb::new();
+        for (name, uid) in parse_entries(content) {
+            db.insert(name, Owner::from_uid(uid));
+        }
+        db
+    }
+
+    /// Adds user with given name and UID.
+    ///
+    /// If several names map to the same UID the first one added is returned by [`UserDb::name_of`], like with `getpwuid`.
+    pub fn insert(&mut self, name: &str, owner: Owner) {
+        self.by_name.insert(name.to_owned(), owner);
+        self.names.entry(owner).or_insert_with(|| name.to_owned());
+    }
+
+    /// Gets Owner by user name.
+    pub fn owner(&self, name: &str) -> Option<Owner> {
+        self.by_name.get(name).copied()
+    }
+
+    /// Gets user name of the given Owner.
+    pub fn name_of(&self, owner: Owner) -> Option<&str> {
+        self.names.get(&owner).map(String::as_str)
+    }
+}
+
+/// Group name to GID mapping independent of the system group database.
+///
+/// Can be parsed from `group(5)` formatted content or built programmatically like [`UserDb`].
+#[derive(Debug, Clone, Default)]
+pub struct GroupDb {
+    by_name: HashMap<String, Group>,
+    names: HashMap<Group, String>,
+}
+
+impl GroupDb {
+    /// Constructs an empty database.
+    pub fn new() -> GroupDb {
+        GroupDb::default()
+    }
+
+    /// Parses `group(5)` formatted content.
+    ///
+    /// Empty lines and comments starting with `#` are ignored, as are malformed lines.
+    pub fn parse(content: &str) -> GroupDb {
+        let mut db = GroupDb::new();
+        for (name, gid) in parse_entries(content) {
+            db.insert(name, Group::from_gid(gid));
+        }
+        db
+    }
+
+    /// Adds group with given name and GID.
+    ///
+    /// If several names map to the same GID the first one added is returned by [`GroupDb::name_of`], like with `getgrgid`.
+    pub fn insert(&mut self, name: &str, group: Group) {
+        self.by_name.insert(name.to_owned(), group);
+        self.names.entry(group).or_insert_with(|| name.to_owned());
+    }
+
+    /// Gets Group by group name.
+    pub fn group(&self, name: &str) -> Option<Group> {
+        self.by_name.get(name).copied()
+    }
+
+    /// Gets group name of the given Group.
+    pub fn name_of(&self, group: Group) -> Option<&str> {
+        self.names.get(&group).map(String::as_str)
+    }
+}
+
+impl Owner {
+    /// Constructs Owner from name looked up in the given in-memory user database.
+    pub fn from_name_in_db(user: &str, db: &UserDb) -> Result<Owner, FileOwnerError> {
+        db.owner(user).ok_or_else(|| FileOwnerError::UserNotFound(user.to_owned()))
+    }
+
+    /// Checks whether both Owners have the same user name in the given in-memory user database, regardless of their UIDs.
+    ///
+    /// Returns [`FileOwnerError::UidNotFound`] if either has no name in the database.
+    pub fn name_eq_in_db(&self, other: &Owner, db: &UserDb) -> Result<bool, FileOwnerError> {
+        let name = |owner: &Owner| db.name_of(*owner).ok_or_else(|| FileOwnerError::UidNotFound(owner.id()));
+        Ok(name(self)? == name(other)?)
+    }
+}
+
+impl Group {
+    /// Constructs Group from name looked up in the given in-memory group database.
+    pub fn from_name_in_db(group: &str, db: &GroupDb) -> Result<Group, FileOwnerError> {
+        db.group(group).ok_or_else(|| FileOwnerError::GroupNotFound(group.to_owned()))
+    }
+
+    /// Checks whether both Groups have the same group name in the given in-memory group database, regardless of their GIDs.
+    ///
+    /// Returns [`FileOwnerError::GidNotFound`] if either has no name in the database.
+    pub fn name_eq_in_db(&self, other: &Group, db: &GroupDb) -> Result<bool, FileOwnerError> {
+        let name = |group: &Group| db.name_of(*group).ok_or_else(|| FileOwnerError::GidNotFound(group.id()));
+        Ok(name(self)? == name(other)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_entries() {
+        let content = "# comment\n\nroot:x:0:0:root:/root:/bin/sh\n  www-data:x:33:33::/var/www:/usr/sbin/nologin\nbroken\nbad:x:nan:0::/:/bin/sh\n:x:5:5::/:/bin/sh\nstaff:x:50:alice,bob\n";
+        assert_eq!(parse_entries(content).collect::<Vec<_>>(), vec![("root", 0), ("www-data", 33), ("staff", 50)]);
+    }
+
+    #[test]
+    fn test_user_db() {
+        let mut db = UserDb::parse("root:x:0:0:root:/root:/bin/sh\ntoor:x:0:0::/root:/bin/sh\n# comment\nbuilder:x:4242:4242::/home/builder:/bin/sh\n");
+        db.insert("extra", Owner::from_uid(5000));
+
+        assert_eq!(Owner::from_name_in_db("builder", &db).unwrap(), Owner::from_uid(4242));
+        assert_eq!(Owner::from_name_in_db("toor", &db).unwrap(), Owner::from_uid(0));
+        assert_eq!(Owner::from_name_in_db("extra", &db).unwrap(), Owner::from_uid(5000));
+        assert!(matches!(Owner::from_name_in_db("nobody", &db), Err(FileOwnerError::UserNotFound(name)) if name == "nobody"));
+
+        assert_eq!(db.name_of(Owner::from_uid(4242)), Some("builder"));
+        assert_eq!(db.name_of(Owner::from_uid(0)), Some("root"));
+        assert_eq!(db.name_of(Owner::from_uid(5000)), Some("extra"));
+        assert_eq!(db.name_of(Owner::from_uid(1)), None);
+    }
+
+    #[test]
+    fn test_group_db() {
+        let mut db = GroupDb::parse("root:x:0:\nbuilders:x:4343:builder\n");
+        db.insert("extra", Group::from_gid(5000));
+
+        assert_eq!(Group::from_name_in_db("builders", &db).unwrap(), Group::from_gid(4343));
+        assert_eq!(Group::from_name_in_db("extra", &db).unwrap(), Group::from_gid(5000));
+        assert!(matches!(Group::from_name_in_db("nogroup", &db), Err(FileOwnerError::GroupNotFound(_))));
+
+        assert_eq!(db.name_of(Group::from_gid(4343)), Some("builders"));
+        assert_eq!(db.name_of(Group::from_gid(1)), None);
+    }
+
+    #[test]
+    fn test_name_eq_in_db() {
+        let users = UserDb::parse("alice:x:1000:1000::/home/alice:/bin/sh\nalice:x:2000:2000::/home/alice:/bin/sh\nbob:x:1001:1001::/home/bob:/bin/sh\n");
+        let (alice, remapped, bob) = (Owner::from_uid(1000), Owner::from_uid(2000), Owner::from_uid(1001));
+        assert!(alice.name_eq_in_db(&remapped, &users).unwrap());
+        assert!(alice.name_eq_in_db(&alice, &users).unwrap());
+        assert!(!alice.name_eq_in_db(&bob, &users).unwrap());
+        assert!(matches!(alice.name_eq_in_db(&Owner::from_uid(3000), &users), Err(FileOwnerError::UidNotFound(3000))));
+
+        let groups = GroupDb::parse("staff:x:50:\nstaff:x:500:\nusers:x:100:\n");
+        assert!(Group::from_gid(50).name_eq_in_db(&Group::from_gid(500), &groups).unwrap());
+        assert!(!Group::from_gid(50).name_eq_in_db(&Group::from_gid(100), &groups).unwrap());
+        assert!(matches!(Group::from_gid(3000).name_eq_in_db(&Group::from_gid(50), &groups), Err(FileOwnerError::GidNotFound(3000))));
+    }
+}
@@ -0,0 +1,70 @@
+//! Parsing of owner, group and `chown` style specifications shared by `FromStr` and [`crate::Resolver`].
+use crate::{FileOwnerError, Group, Owner, OwnerGroupSpec};
+
+/// Parses a numeric UID or else looks the user name up with `from_name`.
+pub(crate) fn parse_owner(s: &str, from_name: impl FnOnce(&str) -> Result<Owner, FileOwnerError>) -> Result<Owner, FileOwnerError> {
+    match s.parse::<u32>() {
+        Ok(uid) => Ok(Owner::from_uid(uid)),
+        Err(_) => from_name(s),
+    }
+}
+
+/// Parses a numeric GID or else looks the group name up with `from_name`.
+pub(crate) fn parse_group(s: &str, from_name: impl FnOnce(&str) -> Result<Group, FileOwnerError>) -> Result<Group, FileOwnerError> {
+    match s.parse::<u32>() {
+        Ok(gid) => Ok(Group::from_gid(gid)),
+        Err(_) => from_name(s),
+    }
+}
+
+/// Parses `owner`, `owner:group`, `owner:`, `:group` or `:` resolving names with the given lookups.
+pub(crate) fn parse_spec(s: &str, owner_from_name: impl FnOnce(&str) -> Result<Owner, FileOwnerError>, group_from_name: impl FnOnce(&str) -> Result<Group, FileOwnerError>) -> Result<OwnerGroupSpec, FileOwnerError> {
+    let (owner, group) = match s.split_once(':') {
+        Some((owner, group)) => (owner, Some(group)),
+        None => (s, None),
+    };
+
+    let owner = if owner.is_empty() { None } else { Some(parse_owner(owner, owner_from_name)?) };
+    let group = match (owner, group) {
+        (_, None) => None,
+        (Some(owner), Some("")) => Some(owner.primary_group()?),
+        (None, Some("")) => None,
+        (_, Some(group)) => Some(parse_group(group, group_from_name)?),
+    };
+
+    Ok(OwnerGroupSpec { owner, group })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn owner_lookup(name: &str) -> Result<Owner, FileOwnerError> {
+        match name {
+            "alice" => Ok(Owner::from_uid(1500)),
+            _ => Err(FileOwnerError::UserNotFound(name.to_owned())),
+        }
+    }
+
+    fn group_lookup(name: &str) -> Result<Group, FileOwnerError> {
+        match name {
+            "staff" => Ok(Group::from_gid(50)),
+            _ => Err(FileOwnerError::GroupNotFound(name.to_owned())),
+        }
+    }
+
+    #[test]
+    fn test_parse_with_lookups() {
+        assert_eq!(parse_owner("42", |_| unreachable!()).unwrap(), Owner::from_uid(42));
+        assert_eq!(parse_owner("alice", owner_lookup).unwrap(), Owner::from_uid(1500));
+        assert!(matches!(parse_owner("-1", owner_lookup), Err(FileOwnerError::UserNotFound(name)) if name == "-1"));
+        assert_eq!(parse_group("42", |_| unreachable!()).unwrap(), Group::from_gid(42));
+        assert_eq!(parse_group("staff", group_lookup).unwrap(), Group::from_gid(50));
+
+        assert_eq!(parse_spec("alice:staff", owner_lookup, group_lookup).unwrap(), OwnerGroupSpec { owner: Some(Owner::from_uid(1500)), group: Some(Group::from_gid(50)) });
+        assert_eq!(parse_spec("7", owner_lookup, group_lookup).unwrap(), OwnerGroupSpec { owner: Some(Owner::from_uid(7)), group: None });
+        assert_eq!(parse_spec(":8", owner_lookup, group_lookup).unwrap(), OwnerGroupSpec { owner: None, group: Some(Group::from_gid(8)) });
+        assert_eq!(parse_spec(":", owner_lookup, group_lookup).unwrap(), OwnerGroupSpec { owner: None, group: None });
+        assert!(matches!(parse_spec("7:nope", owner_lookup, group_lookup), Err(FileOwnerError::GroupNotFound(name)) if name == "nope"));
+    }
+}
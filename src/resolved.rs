@@ -0,0 +1,134 @@
+//! Owner and group values that remember their name after the first lookup.
+use crate::{FileOwnerError, Group, Owner};
+use std::cell::OnceCell;
+
+#[cfg(test)]
+use std::cell::Cell;
+
+/// Owner together with its lazily resolved user name.
+///
+/// [`ResolvedOwner::name`] queries the user database only on the first successful call and returns the remembered name afterwards.
+/// A UID without a user is remembered as `None` too. Errors are not remembered, so the next call tries again.
+/// [`Owner`] stays the cheap `Copy` type; use this when the name of the same owner is needed repeatedly.
+#[derive(Debug, Clone)]
+pub struct ResolvedOwner {
+    owner: Owner,
+    name: OnceCell<Option<String>>,
+    #[cfg(test)]
+    lookups: Cell<usize>,
+}
+
+impl ResolvedOwner {
+    /// Constructs a ResolvedOwner with the name not yet resolved.
+    pub fn new(owner: Owner) -> ResolvedOwner {
+        ResolvedOwner { owner, name: OnceCell::new(), #[cfg(test)] lookups: Cell::new(0) }
+    }
+
+    /// Gets the Owner.
+    pub fn owner(&self) -> Owner {
+        self.owner
+    }
+
+    /// Gets the user name, looking it up on the first call.
+    pub fn name(&self) -> Result<Option<&str>, FileOwnerError> {
+        if let Some(name) = self.name.get() {
+            return Ok(name.as_deref());
+        }
+        #[cfg(test)]
+        self.lookups.set(self.lookups.get() + 1);
+        let name = self.owner.name()?;
+        Ok(self.name.get_or_init(|| name).as_deref())
+    }
+}
+
+impl From<Owner> for ResolvedOwner {
+    fn from(owner: Owner) -> ResolvedOwner {
+        ResolvedOwner::new(owner)
+    }
+}
+
+impl From<ResolvedOwner> for Owner {
+    fn from(resolved: ResolvedOwner) -> Owner {
+        resolved.owner
+    }
+}
+
+/// Group together with its lazily resolved group name.
+///
+/// Behaves like [`ResolvedOwner`] for groups.
+#[derive(Debug, Clone)]
+pub struct ResolvedGroup {
+    group: Group,
+    name: OnceCell<Option<String>>,
+    #[cfg(test)]
+    lookups: Cell<usize>,
+}
+
+impl ResolvedGroup {
+    /// Constructs a ResolvedGroup with the name not yet resolved.
+    pub fn new(group: Group) -> ResolvedGroup {
+        ResolvedGroup { group, name: OnceCell::new(), #[cfg(test)] lookups: Cell::new(0) }
+    }
+
+    /// Gets the Group.
+    pub fn group(&self) -> Group {
+        self.group
+    }
+
+    /// Gets the group name, looking it up on the first call.
+    pub fn name(&self) -> Result<Option<&str>, FileOwnerError> {
+        if let Some(name) = self.name.get() {
+            return Ok(name.as_deref());
+        }
+        #[cfg(test)]
+        self.lookups.set(self.lookups.get() + 1);
+        let name = self.group.name()?;
+        Ok(self.name.get_or_init(|| name).as_deref())
+    }
+}
+
+impl From<Group> for ResolvedGroup {
+    fn from(group: Group) -> ResolvedGroup {
+        ResolvedGroup::new(group)
+    }
+}
+
+impl From<ResolvedGroup> for Group {
+    fn from(resolved: ResolvedGroup) -> Group {
+        resolved.group
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolved_owner() {
+        let resolved = ResolvedOwner::from(Owner::from_name("nobody").unwrap());
+        assert_eq!(resolved.name().unwrap(), Some("nobody"));
+        assert_eq!(resolved.name().unwrap(), Some("nobody"));
+        assert_eq!(resolved.lookups.get(), 1);
+        assert_eq!(Owner::from(resolved), Owner::from_name("nobody").unwrap());
+
+        let orphan = ResolvedOwner::new(Owner::from_uid(321321));
+        assert_eq!(orphan.name().unwrap(), None);
+        assert_eq!(orphan.name().unwrap(), None);
+        assert_eq!(orphan.lookups.get(), 1);
+        assert_eq!(orphan.owner(), Owner::from_uid(321321));
+    }
+
+    #[test]
+    fn test_resolved_group() {
+        let resolved = ResolvedGroup::from(Group::from_name("nogroup").unwrap());
+        assert_eq!(resolved.name().unwrap(), Some("nogroup"));
+        assert_eq!(resolved.clone().name().unwrap(), Some("nogroup"));
+        assert_eq!(resolved.lookups.get(), 1);
+        assert_eq!(resolved.group(), Group::from_name("nogroup").unwrap());
+
+        let orphan = ResolvedGroup::new(Group::from_gid(321321));
+        assert_eq!(orphan.name().unwrap(), None);
+        assert_eq!(orphan.name().unwrap(), None);
+        assert_eq!(orphan.lookups.get(), 1);
+    }
+}
@@ -0,0 +1,249 @@
+//! Serde support for ownership types.
+//!
+//! Owners and groups are serialized as numeric ids rather than names: ids need no user database lookup and round-trip exactly,
+//! but the same id may map to a different user on another machine.
+//! Deserialization accepts either a numeric id or a name, which is resolved against the local user or group database.
+//!
+//! The [`as_name`] and [`as_id`] modules can be used with `#[serde(with = "...")]` on `Owner` or `Group` fields to choose the representation explicitly:
+//!
+//! ```
+//! # #[cfg(feature = "serde")] {
+//! use file_owner::{Group, Owner};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Entry {
+//!     #[serde(with = "file_owner::serde::as_name")]
+//!     owner: Owner,
+//!     #[serde(with = "file_owner::serde::as_id")]
+//!     group: Group,
+//! }
+//! # }
+//! ```
+use crate::{FileOwnerError, Group, Owner};
+use ::serde::de::{self, Deserialize, Deserializer, Visitor};
+use ::serde::ser::{Serialize, Serializer};
+use std::convert::TryFrom;
+use std::fmt;
+
+impl Serialize for Owner {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(self.id())
+    }
+}
+
+impl<'de> Deserialize<'de> for Owner {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Owner, D::Error> {
+        deserializer.deserialize_any(IdOrNameVisitor::new())
+    }
+}
+
+impl Serialize for Group {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(self.id())
+    }
+}
+
+impl<'de> Deserialize<'de> for Group {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Group, D::Error> {
+        deserializer.deserialize_any(IdOrNameVisitor::new())
+    }
+}
+
+mod sealed {
+    use crate::FileOwnerError;
+
+    /// Owner or Group.
+    pub trait Entry: Copy + From<u32> + std::str::FromStr<Err = FileOwnerError> {
+        const EXPECTING: &'static str;
+        fn id(&self) -> u32;
+        fn name(&self) -> Result<Option<String>, FileOwnerError>;
+        fn not_found(&self) -> FileOwnerError;
+    }
+}
+use sealed::Entry;
+
+impl Entry for Owner {
+    const EXPECTING: &'static str = "user name or UID";
+
+    fn id(&self) -> u32 {
+        Owner::id(self)
+    }
+
+    fn name(&self) -> Result<Option<String>, FileOwnerError> {
+        Owner::name(self)
+    }
+
+    fn not_found(&self) -> FileOwnerError {
+        FileOwnerError::UidNotFound(Owner::id(self))
+    }
+}
+
+impl Entry for Group {
+    const EXPECTING: &'static str = "group name or GID";
+
+    fn id(&self) -> u32 {
+        Group::id(self)
+    }
+
+    fn name(&self) -> Result<Option<String>, FileOwnerError> {
+        Group::name(self)
+    }
+
+    fn not_found(&self) -> FileOwnerError {
+        FileOwnerError::GidNotFound(Group::id(self))
+    }
+}
+
+/// Serializes `Owner` or `Group` as user or group name.
+///
+/// Serialization fails if the id is not assigned to a name. Deserialization accepts a name or a numeric id.
+pub mod as_name {
+    use super::{Entry, IdOrNameVisitor};
+    use ::serde::{ser, Deserializer, Serializer};
+
+    /// Serializes as name; use with `#[serde(with = "file_owner::serde::as_name")]`.
+    pub fn serialize<T: Entry, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        let name = value.name().map_err(ser::Error::custom)?.ok_or_else(|| ser::Error::custom(value.not_found()))?;
+        serializer.serialize_str(&name)
+    }
+
+    /// Deserializes from name or id; use with `#[serde(with = "file_owner::serde::as_name")]`.
+    pub fn deserialize<'de, T: Entry, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+        deserializer.deserialize_any(IdOrNameVisitor::new())
+    }
+}
+
+/// Serializes `Owner` or `Group` as numeric id.
+///
+/// Deserialization accepts only a numeric id and never looks up names.
+pub mod as_id {
+    use super::Entry;
+    use ::serde::{Deserialize, Deserializer, Serializer};
+
+    /// Serializes as numeric id; use with `#[serde(with = "file_owner::serde::as_id")]`.
+    pub fn serialize<T: Entry, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(value.id())
+    }
+
+    /// Deserializes from numeric id; use with `#[serde(with = "file_owner::serde::as_id")]`.
+    pub fn deserialize<'de, T: Entry, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+        u32::deserialize(deserializer).map(T::from)
+    }
+}
+
+/// Accepts an unsigned 32-bit integer or a name or id string parsed with `FromStr`.
+struct IdOrNameVisitor<T>(std::marker::PhantomData<T>);
+
+impl<T> IdOrNameVisitor<T> {
+    fn new() -> IdOrNameVisitor<T> {
+        IdOrNameVisitor(std::marker::PhantomData)
+    }
+}
+
+impl<T: Entry> Visitor<'_> for IdOrNameVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(T::EXPECTING)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<T, E> {
+        u32::try_from(v).map(T::from).map_err(|_| E::invalid_value(de::Unexpected::Unsigned(v), &self))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<T, E> {
+        u32::try_from(v).map(T::from).map_err(|_| E::invalid_value(de::Unexpected::Signed(v), &self))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+        v.parse().map_err(E::custom)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::Ownership;
+    use ::serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Named {
+        #[serde(with = "as_name")]
+        owner: Owner,
+        #[serde(with = "as_name")]
+        group: Group,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Numeric {
+        #[serde(with = "as_id")]
+        owner: Owner,
+        #[serde(with = "as_id")]
+        group: Group,
+    }
+
+    #[test]
+    fn test_owner_round_trip() {
+        let nobody = Owner::from_name("nobody").unwrap();
+        let json = serde_json::to_string(&nobody).unwrap();
+        assert_eq!(json, nobody.id().to_string());
+        assert_eq!(serde_json::from_str::<Owner>(&json).unwrap(), nobody);
+
+        assert_eq!(serde_json::from_str::<Owner>("\"nobody\"").unwrap(), nobody);
+        assert_eq!(serde_json::from_str::<Owner>("\"321321\"").unwrap(), Owner::from_uid(321321));
+        assert_eq!(serde_json::from_str::<Owner>("321321").unwrap(), Owner::from_uid(321321));
+    }
+
+    #[test]
+    fn test_owner_errors() {
+        let err = serde_json::from_str::<Owner>("\"nonexistent-user\"").unwrap_err();
+        assert!(err.to_string().contains("user name \"nonexistent-user\" not found"), "{}", err);
+        assert!(serde_json::from_str::<Owner>("-1").is_err());
+        assert!(serde_json::from_str::<Owner>("4294967296").is_err());
+        assert!(serde_json::from_str::<Owner>("1.5").is_err());
+    }
+
+    #[test]
+    fn test_group_round_trip() {
+        let nogroup = Group::from_name("nogroup").unwrap();
+        let json = serde_json::to_string(&nogroup).unwrap();
+        assert_eq!(json, nogroup.id().to_string());
+        assert_eq!(serde_json::from_str::<Group>(&json).unwrap(), nogroup);
+        assert_eq!(serde_json::from_str::<Group>("\"nogroup\"").unwrap(), nogroup);
+
+        let err = serde_json::from_str::<Group>("\"nonexistent-group\"").unwrap_err();
+        assert!(err.to_string().contains("group name \"nonexistent-group\" not found"), "{}", err);
+    }
+
+    #[test]
+    fn test_ownership() {
+        let ownership = serde_json::from_str::<Ownership>(r#"{"owner":"nobody","group":"nogroup"}"#).unwrap();
+        assert_eq!(ownership, Ownership::from_names("nobody", "nogroup").unwrap());
+
+        let json = serde_json::to_string(&ownership).unwrap();
+        assert_eq!(json, format!(r#"{{"owner":{},"group":{}}}"#, ownership.owner.id(), ownership.group.id()));
+        assert_eq!(serde_json::from_str::<Ownership>(&json).unwrap(), ownership);
+    }
+
+    #[test]
+    fn test_as_name() {
+        let named = Named { owner: Owner::from_name("nobody").unwrap(), group: Group::from_name("nogroup").unwrap() };
+        let json = serde_json::to_string(&named).unwrap();
+        assert_eq!(json, r#"{"owner":"nobody","group":"nogroup"}"#);
+        assert_eq!(serde_json::from_str::<Named>(&json).unwrap(), named);
+
+        let orphan = Named { owner: Owner::from_uid(321321), group: Group::from_name("nogroup").unwrap() };
+        let err = serde_json::to_string(&orphan).unwrap_err();
+        assert!(err.to_string().contains("no user with UID 321321"), "{}", err);
+    }
+
+    #[test]
+    fn test_as_id() {
+        let numeric = Numeric { owner: Owner::from_uid(321321), group: Group::from_gid(321322) };
+        let json = serde_json::to_string(&numeric).unwrap();
+        assert_eq!(json, r#"{"owner":321321,"group":321322}"#);
+        assert_eq!(serde_json::from_str::<Numeric>(&json).unwrap(), numeric);
+        assert!(serde_json::from_str::<Numeric>(r#"{"owner":"nobody","group":0}"#).is_err());
+    }
+}
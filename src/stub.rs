@@ -0,0 +1,890 @@
+//! Stub of the public API for non-Unix targets.
+//!
+//! Everything compiles but is runtime-inert: operations that would query the user database or a file
+//! return [`FileOwnerError::Unsupported`]. Operations on numeric ids that need no system support work as on Unix.
+//! Items that only make sense on Unix are not provided and are marked "Available on Unix only" in their docs:
+//! the `nix` conversions `Owner::as_uid`, `Group::as_gid`, `Owner::user` and `FileOwnerError::errno`,
+//! the process identity functions `current_owner`, `current_group`, `real_owner` and `real_group`,
+//! `Ownership::from_metadata` which reads Unix metadata fields, and the `RawFd` based functions
+//! `set_owner_fd`, `set_owner_fd_path`, `set_owner_at`, `owner_fd`, `group_fd` and `owner_group_fd`.
+use std::convert::{TryFrom, TryInto};
+use std::fmt::{self, Display};
+use std::fs::{File, Metadata};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use crate::FileOwnerError;
+use crate::parse::{parse_group, parse_owner, parse_spec};
+
+fn unsupported<T>() -> Result<T, FileOwnerError> {
+    Err(FileOwnerError::Unsupported)
+}
+
+/// First UID of regular (non-system) user accounts, as conventionally used by Linux distributions.
+pub const SYSTEM_UID_LIMIT: u32 = 1000;
+
+/// Owner of a file.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct Owner(u32);
+
+impl Owner {
+    /// Constructs Owner from UID.
+    pub fn from_uid(uid: u32) -> Owner {
+        Owner(uid)
+    }
+
+    /// Constructs Owner from UID as the platform native type.
+    pub fn from_raw(uid: u32) -> Owner {
+        Owner(uid)
+    }
+
+    /// Construct Owner from name.
+    pub fn from_name(_user: &str) -> Result<Owner, FileOwnerError> {
+        unsupported()
+    }
+
+    /// Constructs Owner from name looked up in the given `passwd(5)` formatted file.
+    pub fn from_name_in(_user: &str, _passwd_path: &Path) -> Result<Owner, FileOwnerError> {
+        unsupported()
+    }
+
+    /// Gets UID.
+    pub fn id(&self) -> u32 {
+        self.0
+    }
+
+    /// Gets UID as the platform native type.
+    pub fn as_raw(&self) -> u32 {
+        self.0
+    }
+
+    /// Gets UID checking that it fits into `u32` without reinterpretation.
+    pub fn try_id(&self) -> Result<u32, FileOwnerError> {
+        Ok(self.0)
+    }
+
+    /// Gets name if assigned to UID.
+    pub fn name(&self) -> Result<Option<String>, FileOwnerError> {
+        unsupported()
+    }
+
+    /// Checks whether both have the same user name, regardless of their UIDs.
+    pub fn name_eq(&self, _other: &Owner) -> Result<bool, FileOwnerError> {
+        unsupported()
+    }
+
+    /// Gets UID together with the name if assigned.
+    pub fn resolve(&self) -> Result<(u32, Option<String>), FileOwnerError> {
+        unsupported()
+    }
+
+    /// Gets name if assigned to UID, or UID as a string otherwise.
+    pub fn name_or_id(&self) -> Result<String, FileOwnerError> {
+        unsupported()
+    }
+
+    /// Checks whether UID is 0 (root).
+    pub fn is_root(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Checks whether UID is below [`SYSTEM_UID_LIMIT`] which conventionally marks system accounts.
+    pub fn is_system(&self) -> bool {
+        self.0 < SYSTEM_UID_LIMIT
+    }
+
+    /// Checks whether UID is assigned to a user in the user database.
+    pub fn exists(&self) -> Result<bool, FileOwnerError> {
+        unsupported()
+    }
+
+    /// Gets home directory if UID is assigned to a user.
+    pub fn home_dir(&self) -> Result<Option<PathBuf>, FileOwnerError> {
+        unsupported()
+    }
+
+    /// Gets login shell if UID is assigned to a user.
+    pub fn shell(&self) -> Result<Option<String>, FileOwnerError> {
+        unsupported()
+    }
+
+    /// Gets GECOS field (usually full name) if UID is assigned to a user.
+    pub fn gecos(&self) -> Result<Option<String>, FileOwnerError> {
+        unsupported()
+    }
+
+    /// Gets primary (login) group of the user assigned to UID.
+    pub fn primary_group(&self) -> Result<Group, FileOwnerError> {
+        unsupported()
+    }
+
+    /// Gets all users of the user database.
+    pub fn all() -> Result<Vec<Owner>, FileOwnerError> {
+        unsupported()
+    }
+
+    /// Gets all groups the user assigned to UID is a member of, including the primary group.
+    pub fn groups(&self) -> Result<Vec<Group>, FileOwnerError> {
+        unsupported()
+    }
+}
+
+impl From<u32> for Owner {
+    fn from(uid: u32) -> Owner {
+        Owner::from_uid(uid)
+    }
+}
+
+impl<'s> TryFrom<&'s str> for Owner {
+    type Error = FileOwnerError;
+
+    fn try_from(name: &'s str) -> Result<Owner, Self::Error> {
+        Owner::from_name(name)
+    }
+}
+
+impl FromStr for Owner {
+    type Err = FileOwnerError;
+
+    fn from_str(s: &str) -> Result<Owner, Self::Err> {
+        parse_owner(s, Owner::from_name)
+    }
+}
+
+/// Displays UID as names cannot be looked up.
+impl Display for Owner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl PartialEq<u32> for Owner {
+    fn eq(&self, other: &u32) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<Owner> for u32 {
+    fn eq(&self, other: &Owner) -> bool {
+        other == self
+    }
+}
+
+/// Group of a file.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct Group(u32);
+
+impl Group {
+    /// Constructs Group from GID.
+    pub fn from_gid(gid: u32) -> Group {
+        Group(gid)
+    }
+
+    /// Constructs Group from GID as the platform native type.
+    pub fn from_raw(gid: u32) -> Group {
+        Group(gid)
+    }
+
+    /// Constructs Group from name.
+    pub fn from_name(_group: &str) -> Result<Group, FileOwnerError> {
+        unsupported()
+    }
+
+    /// Constructs Group from name looked up in the given `group(5)` formatted file.
+    pub fn from_name_in(_group: &str, _group_path: &Path) -> Result<Group, FileOwnerError> {
+        unsupported()
+    }
+
+    /// Gets GID.
+    pub fn id(&self) -> u32 {
+        self.0
+    }
+
+    /// Gets GID as the platform native type.
+    pub fn as_raw(&self) -> u32 {
+        self.0
+    }
+
+    /// Gets GID checking that it fits into `u32` without reinterpretation.
+    pub fn try_id(&self) -> Result<u32, FileOwnerError> {
+        Ok(self.0)
+    }
+
+    /// Gets name if assigned to GID.
+    pub fn name(&self) -> Result<Option<String>, FileOwnerError> {
+        unsupported()
+    }
+
+    /// Checks whether both have the same group name, regardless of their GIDs.
+    pub fn name_eq(&self, _other: &Group) -> Result<bool, FileOwnerError> {
+        unsupported()
+    }
+
+    /// Gets GID together with the name if assigned.
+    pub fn resolve(&self) -> Result<(u32, Option<String>), FileOwnerError> {
+        unsupported()
+    }
+
+    /// Gets name if assigned to GID, or GID as a string otherwise.
+    pub fn name_or_id(&self) -> Result<String, FileOwnerError> {
+        unsupported()
+    }
+
+    /// Checks whether GID is 0 (root group).
+    pub fn is_root(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Checks whether GID is assigned to a group in the group database.
+    pub fn exists(&self) -> Result<bool, FileOwnerError> {
+        unsupported()
+    }
+
+    /// Gets all groups of the group database.
+    pub fn all() -> Result<Vec<Group>, FileOwnerError> {
+        unsupported()
+    }
+
+    /// Gets names of all group database entries with this GID, in database order.
+    pub fn names_for_gid(&self) -> Result<Vec<String>, FileOwnerError> {
+        unsupported()
+    }
+
+    /// Gets names of all entries with this GID in the given `group(5)` formatted file instead of the system group database.
+    pub fn names_for_gid_in(&self, _group_path: &Path) -> Result<Vec<String>, FileOwnerError> {
+        unsupported()
+    }
+
+    /// Checks whether the user assigned to the given UID is a member of the group assigned to GID.
+    pub fn contains(&self, _owner: Owner) -> Result<bool, FileOwnerError> {
+        unsupported()
+    }
+
+    /// Gets names of the users listed as members of the group assigned to GID.
+    pub fn members(&self) -> Result<Vec<String>, FileOwnerError> {
+        unsupported()
+    }
+}
+
+impl From<u32> for Group {
+    fn from(gid: u32) -> Group {
+        Group::from_gid(gid)
+    }
+}
+
+impl<'s> TryFrom<&'s str> for Group {
+    type Error = FileOwnerError;
+
+    fn try_from(name: &'s str) -> Result<Group, Self::Error> {
+        Group::from_name(name)
+    }
+}
+
+impl FromStr for Group {
+    type Err = FileOwnerError;
+
+    fn from_str(s: &str) -> Result<Group, Self::Err> {
+        parse_group(s, Group::from_name)
+    }
+}
+
+/// Displays GID as names cannot be looked up.
+impl Display for Group {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl PartialEq<u32> for Group {
+    fn eq(&self, other: &u32) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<Group> for u32 {
+    fn eq(&self, other: &Group) -> bool {
+        other == self
+    }
+}
+
+/// Owner and group of a file.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct Ownership {
+    pub owner: Owner,
+    pub group: Group,
+}
+
+impl Ownership {
+    /// Constructs Ownership from Owner and Group.
+    pub fn new(owner: Owner, group: Group) -> Ownership {
+        Ownership { owner, group }
+    }
+
+    /// Constructs Ownership from UID and GID.
+    pub fn from_ids(uid: u32, gid: u32) -> Ownership {
+        Ownership::new(Owner::from_uid(uid), Group::from_gid(gid))
+    }
+
+    /// Constructs Ownership from user and group names.
+    pub fn from_names(user: &str, group: &str) -> Result<Ownership, FileOwnerError> {
+        Ok(Ownership::new(Owner::from_name(user)?, Group::from_name(group)?))
+    }
+
+    /// Compares with other Ownership by UID and GID.
+    pub fn diff(&self, other: &Ownership) -> OwnershipDiff {
+        match (self.owner == other.owner, self.group == other.group) {
+            (true, true) => OwnershipDiff::Same,
+            (false, true) => OwnershipDiff::Owner,
+            (true, false) => OwnershipDiff::Group,
+            (false, false) => OwnershipDiff::Both,
+        }
+    }
+}
+
+impl From<(Owner, Group)> for Ownership {
+    fn from((owner, group): (Owner, Group)) -> Ownership {
+        Ownership::new(owner, group)
+    }
+}
+
+/// Which parts of two [`Ownership`]s differ.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum OwnershipDiff {
+    /// Both owner and group are the same.
+    Same,
+    /// Only the owner differs.
+    Owner,
+    /// Only the group differs.
+    Group,
+    /// Both owner and group differ.
+    Both,
+}
+
+impl OwnershipDiff {
+    /// Checks whether owner and group are the same.
+    pub fn is_same(&self) -> bool {
+        *self == OwnershipDiff::Same
+    }
+}
+
+/// Owner and group specification as accepted by `chown`, with either part optional.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct OwnerGroupSpec {
+    pub owner: Option<Owner>,
+    pub group: Option<Group>,
+}
+
+impl FromStr for OwnerGroupSpec {
+    type Err = FileOwnerError;
+
+    fn from_str(s: &str) -> Result<OwnerGroupSpec, Self::Err> {
+        parse_spec(s, Owner::from_name, Group::from_name)
+    }
+}
+
+/// Checks whether the running process is likely allowed to change owner of arbitrary files.
+///
+/// Always `false` on this platform.
+pub fn can_change_owner() -> bool {
+    false
+}
+
+/// Runs `f` with the effective UID temporarily set to 0 (root) and restores the previous effective UID afterwards.
+pub fn with_privileges<T>(_f: impl FnOnce() -> Result<T, FileOwnerError>) -> Result<T, FileOwnerError> {
+    unsupported()
+}
+
+/// Sets owner to file at the given path.
+pub fn set_owner<E: Into<FileOwnerError>>(_path: impl AsRef<Path>, _owner: impl TryInto<Owner, Error = E>) -> Result<(), FileOwnerError> {
+    unsupported()
+}
+
+/// Sets owner to file at the given path without following symlinks.
+pub fn set_owner_no_follow<E: Into<FileOwnerError>>(_path: impl AsRef<Path>, _owner: impl TryInto<Owner, Error = E>) -> Result<(), FileOwnerError> {
+    unsupported()
+}
+
+/// Sets owner to file at the given path, following symlinks if `follow` is `true`.
+pub fn set_owner_with<E: Into<FileOwnerError>>(_path: impl AsRef<Path>, _owner: impl TryInto<Owner, Error = E>, _follow: bool) -> Result<(), FileOwnerError> {
+    unsupported()
+}
+
+/// Sets group to file at the given path.
+pub fn set_group<E: Into<FileOwnerError>>(_path: impl AsRef<Path>, _group: impl TryInto<Group, Error = E>) -> Result<(), FileOwnerError> {
+    unsupported()
+}
+
+/// Sets group to file at the given path without following symlinks.
+pub fn set_group_no_follow<E: Into<FileOwnerError>>(_path: impl AsRef<Path>, _group: impl TryInto<Group, Error = E>) -> Result<(), FileOwnerError> {
+    unsupported()
+}
+
+/// Sets group to file at the given path, following symlinks if `follow` is `true`.
+pub fn set_group_with<E: Into<FileOwnerError>>(_path: impl AsRef<Path>, _group: impl TryInto<Group, Error = E>, _follow: bool) -> Result<(), FileOwnerError> {
+    unsupported()
+}
+
+/// Sets owner and group to file at the given path.
+pub fn set_owner_group<E1: Into<FileOwnerError>, E2: Into<FileOwnerError>>(_path: impl AsRef<Path>, _owner: impl TryInto<Owner, Error = E1>, _group: impl TryInto<Group, Error = E2>) -> Result<(), FileOwnerError> {
+    unsupported()
+}
+
+/// Sets owner and group to file at the given path without following symlinks.
+pub fn set_owner_group_no_follow<E1: Into<FileOwnerError>, E2: Into<FileOwnerError>>(_path: impl AsRef<Path>, _owner: impl TryInto<Owner, Error = E1>, _group: impl TryInto<Group, Error = E2>) -> Result<(), FileOwnerError> {
+    unsupported()
+}
+
+/// Sets owner and group to file at the given path, following symlinks if `follow` is `true`.
+pub fn set_owner_group_with<E1: Into<FileOwnerError>, E2: Into<FileOwnerError>>(_path: impl AsRef<Path>, _owner: impl TryInto<Owner, Error = E1>, _group: impl TryInto<Group, Error = E2>, _follow: bool) -> Result<(), FileOwnerError> {
+    unsupported()
+}
+
+/// Sets owner to the given open file.
+pub fn set_owner_file<E: Into<FileOwnerError>>(_file: &File, _owner: impl TryInto<Owner, Error = E>) -> Result<(), FileOwnerError> {
+    unsupported()
+}
+
+/// Sets owner to many files.
+pub fn set_owner_many<E: Into<FileOwnerError>>(_paths: impl IntoIterator<Item = impl AsRef<Path>>, _owner: impl TryInto<Owner, Error = E>) -> Result<usize, Vec<(PathBuf, FileOwnerError)>> {
+    Err(vec![(PathBuf::new(), FileOwnerError::Unsupported)])
+}
+
+/// Gets owner of a file at the given path.
+pub fn owner(_path: impl AsRef<Path>) -> Result<Owner, FileOwnerError> {
+    unsupported()
+}
+
+/// Gets owner of a file at the given path without following symlinks.
+pub fn owner_no_follow(_path: impl AsRef<Path>) -> Result<Owner, FileOwnerError> {
+    unsupported()
+}
+
+/// Gets group of a file at the given path.
+pub fn group(_path: impl AsRef<Path>) -> Result<Group, FileOwnerError> {
+    unsupported()
+}
+
+/// Gets group of a file at the given path without following symlinks.
+pub fn group_no_follow(_path: impl AsRef<Path>) -> Result<Group, FileOwnerError> {
+    unsupported()
+}
+
+/// Gets owner and group of a file at the given path.
+pub fn owner_group(_path: impl AsRef<Path>) -> Result<(Owner, Group), FileOwnerError> {
+    unsupported()
+}
+
+/// Gets owner of the given open file.
+pub fn owner_file(_file: &File) -> Result<Owner, FileOwnerError> {
+    unsupported()
+}
+
+/// Gets group of the given open file.
+pub fn group_file(_file: &File) -> Result<Group, FileOwnerError> {
+    unsupported()
+}
+
+/// Gets owner and group of the given open file.
+pub fn owner_group_file(_file: &File) -> Result<(Owner, Group), FileOwnerError> {
+    unsupported()
+}
+
+/// Gets owner and group of each file at the given paths, in the same order.
+pub fn owner_group_many<P: AsRef<Path>>(paths: impl IntoIterator<Item = P>) -> Vec<Result<(Owner, Group), FileOwnerError>> {
+    paths.into_iter().map(owner_group).collect()
+}
+
+/// Gets owner and group of a file at the given path without following symlinks.
+pub fn owner_group_no_follow(_path: impl AsRef<Path>) -> Result<(Owner, Group), FileOwnerError> {
+    unsupported()
+}
+
+/// Gets ownership of a file at the given path.
+pub fn ownership(_path: impl AsRef<Path>) -> Result<Ownership, FileOwnerError> {
+    unsupported()
+}
+
+/// Gets owner, group and permission bits of a file at the given path.
+pub fn ownership_mode(_path: impl AsRef<Path>) -> Result<(Owner, Group, u32), FileOwnerError> {
+    unsupported()
+}
+
+/// Checks whether file at the given path is owned by the given user, following symlinks.
+pub fn is_owned_by<E: Into<FileOwnerError>>(_path: impl AsRef<Path>, _owner: impl TryInto<Owner, Error = E>) -> Result<bool, FileOwnerError> {
+    unsupported()
+}
+
+/// Checks whether file at the given path belongs to the given group, following symlinks.
+pub fn is_group<E: Into<FileOwnerError>>(_path: impl AsRef<Path>, _group: impl TryInto<Group, Error = E>) -> Result<bool, FileOwnerError> {
+    unsupported()
+}
+
+/// Sets ownership to file at the given path.
+pub fn set_ownership(_path: impl AsRef<Path>, _ownership: &Ownership) -> Result<(), FileOwnerError> {
+    unsupported()
+}
+
+/// Copies owner and group of file at `src` path to file at `dst` path.
+pub fn copy_ownership(_src: impl AsRef<Path>, _dst: impl AsRef<Path>) -> Result<(), FileOwnerError> {
+    unsupported()
+}
+
+/// Copies owner and group of file at `src` path to file at `dst` path without following symlinks on either path.
+pub fn copy_ownership_no_follow(_src: impl AsRef<Path>, _dst: impl AsRef<Path>) -> Result<(), FileOwnerError> {
+    unsupported()
+}
+
+/// Applies owner and group of the parent directory of `path` to `path` itself, e.g. for files extracted from an archive.
+pub fn inherit_parent_ownership(_path: impl AsRef<Path>) -> Result<(), FileOwnerError> {
+    unsupported()
+}
+
+/// Checks whether files at paths `a` and `b` have the same owner and group.
+pub fn same_ownership(_a: impl AsRef<Path>, _b: impl AsRef<Path>) -> Result<bool, FileOwnerError> {
+    unsupported()
+}
+
+/// Compares owner and group of files at paths `a` and `b`.
+pub fn diff_ownership(_a: impl AsRef<Path>, _b: impl AsRef<Path>) -> Result<OwnershipDiff, FileOwnerError> {
+    unsupported()
+}
+
+/// Exchanges owner and group of files at paths `a` and `b` and returns their previous ownership.
+pub fn swap_ownership(_a: impl AsRef<Path>, _b: impl AsRef<Path>) -> Result<(Ownership, Ownership), FileOwnerError> {
+    unsupported()
+}
+
+/// Sets owner and then group of a file at the given path, restoring the original owner if setting the group fails.
+pub fn set_owner_then_group_rollback<E1: Into<FileOwnerError>, E2: Into<FileOwnerError>>(_path: impl AsRef<Path>, _owner: impl TryInto<Owner, Error = E1>, _group: impl TryInto<Group, Error = E2>) -> Result<(), FileOwnerError> {
+    unsupported()
+}
+
+/// Copies contents of file at `src` path to `dst` path and then applies the owner and group of `src` to `dst`.
+pub fn copy_with_ownership(_src: impl AsRef<Path>, _dst: impl AsRef<Path>) -> Result<u64, FileOwnerError> {
+    unsupported()
+}
+
+/// Sets owner and/or group to file at the given path as specified.
+pub fn set_spec(_path: impl AsRef<Path>, _spec: &OwnerGroupSpec) -> Result<(), FileOwnerError> {
+    unsupported()
+}
+
+/// Applies owner/group specifications to their paths, continuing past errors.
+pub fn apply_manifest(entries: impl IntoIterator<Item = (PathBuf, OwnerGroupSpec)>) -> Result<(), Vec<(PathBuf, FileOwnerError)>> {
+    let errors = entries.into_iter().map(|(path, _spec)| (path, FileOwnerError::Unsupported)).collect::<Vec<_>>();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Sets owner to file at the given path checking first that the UID is assigned to a user.
+pub fn set_owner_checked<E: Into<FileOwnerError>>(_path: impl AsRef<Path>, _owner: impl TryInto<Owner, Error = E>) -> Result<(), FileOwnerError> {
+    unsupported()
+}
+
+/// Sets owner to file at the given path after resolving it with `fs::canonicalize`.
+pub fn set_owner_canonical<E: Into<FileOwnerError>>(_path: impl AsRef<Path>, _owner: impl TryInto<Owner, Error = E>) -> Result<(), FileOwnerError> {
+    unsupported()
+}
+
+/// Sets owner to file at the given path only if it differs from the current one.
+pub fn set_owner_if_changed<E: Into<FileOwnerError>>(_path: impl AsRef<Path>, _owner: impl TryInto<Owner, Error = E>) -> Result<bool, FileOwnerError> {
+    unsupported()
+}
+
+/// Sets group to file at the given path only if it differs from the current one.
+pub fn set_group_if_changed<E: Into<FileOwnerError>>(_path: impl AsRef<Path>, _group: impl TryInto<Group, Error = E>) -> Result<bool, FileOwnerError> {
+    unsupported()
+}
+
+/// Sets owner and group to file at the given path only if either differs from the current ones.
+pub fn set_owner_group_if_changed<E1: Into<FileOwnerError>, E2: Into<FileOwnerError>>(_path: impl AsRef<Path>, _owner: impl TryInto<Owner, Error = E1>, _group: impl TryInto<Group, Error = E2>) -> Result<bool, FileOwnerError> {
+    unsupported()
+}
+
+/// Sets owner to file at the given path and reports the change to `log`.
+pub fn set_owner_logged<E: Into<FileOwnerError>>(_path: impl AsRef<Path>, _owner: impl TryInto<Owner, Error = E>, _log: impl FnMut(&Path, Owner, Owner)) -> Result<(), FileOwnerError> {
+    unsupported()
+}
+
+/// Sets group to file at the given path and reports the change to `log`.
+pub fn set_group_logged<E: Into<FileOwnerError>>(_path: impl AsRef<Path>, _group: impl TryInto<Group, Error = E>, _log: impl FnMut(&Path, Group, Group)) -> Result<(), FileOwnerError> {
+    unsupported()
+}
+
+/// Sets owner and group to file at the given path and reports the change to `log`.
+pub fn set_owner_group_logged<E1: Into<FileOwnerError>, E2: Into<FileOwnerError>>(_path: impl AsRef<Path>, _owner: impl TryInto<Owner, Error = E1>, _group: impl TryInto<Group, Error = E2>, _log: impl FnMut(&Path, Ownership, Ownership)) -> Result<(), FileOwnerError> {
+    unsupported()
+}
+
+/// Ownership change of a file computed without applying it.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct OwnershipChange {
+    pub path: PathBuf,
+    pub current: Ownership,
+    pub proposed: Ownership,
+}
+
+impl OwnershipChange {
+    /// Checks whether applying the change would modify the ownership.
+    pub fn is_change(&self) -> bool {
+        self.current != self.proposed
+    }
+
+    /// Applies the proposed ownership to the file.
+    pub fn apply(&self) -> Result<(), FileOwnerError> {
+        unsupported()
+    }
+}
+
+/// Computes the change [`set_owner`] would make to file at the given path without applying it.
+pub fn plan_set_owner<E: Into<FileOwnerError>>(_path: impl AsRef<Path>, _owner: impl TryInto<Owner, Error = E>) -> Result<OwnershipChange, FileOwnerError> {
+    unsupported()
+}
+
+/// Sets owner to file at the given path and returns the previous owner.
+pub fn replace_owner<E: Into<FileOwnerError>>(_path: impl AsRef<Path>, _owner: impl TryInto<Owner, Error = E>) -> Result<Owner, FileOwnerError> {
+    unsupported()
+}
+
+/// Sets group to file at the given path and returns the previous group.
+pub fn replace_group<E: Into<FileOwnerError>>(_path: impl AsRef<Path>, _group: impl TryInto<Group, Error = E>) -> Result<Group, FileOwnerError> {
+    unsupported()
+}
+
+/// Sets owner and group to file at the given path and returns the previous owner and group.
+pub fn replace_owner_group<E1: Into<FileOwnerError>, E2: Into<FileOwnerError>>(_path: impl AsRef<Path>, _owner: impl TryInto<Owner, Error = E1>, _group: impl TryInto<Group, Error = E2>) -> Result<(Owner, Group), FileOwnerError> {
+    unsupported()
+}
+
+/// How symbolic links are handled by recursive operations.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum SymlinkPolicy {
+    /// Leaves symbolic links untouched.
+    Skip,
+    /// Changes ownership of the symbolic link itself and does not descend into it.
+    #[default]
+    NoFollow,
+    /// Changes ownership of the symbolic link target and descends into it if it is a directory.
+    Follow,
+}
+
+/// Sets owner to all files in the directory tree at the given path, including the path itself.
+pub fn set_owner_recursive<E: Into<FileOwnerError>>(_path: impl AsRef<Path>, _owner: impl TryInto<Owner, Error = E>) -> Result<(), FileOwnerError> {
+    unsupported()
+}
+
+/// Sets owner to all files in the directory tree at the given path using given symlink policy.
+pub fn set_owner_recursive_with<E: Into<FileOwnerError>>(_path: impl AsRef<Path>, _owner: impl TryInto<Owner, Error = E>, _policy: SymlinkPolicy) -> Result<(), FileOwnerError> {
+    unsupported()
+}
+
+/// Sets owner and group to all files in the directory tree at the given path, including the path itself.
+pub fn set_owner_group_recursive<E1: Into<FileOwnerError>, E2: Into<FileOwnerError>>(_path: impl AsRef<Path>, _owner: impl TryInto<Owner, Error = E1>, _group: impl TryInto<Group, Error = E2>) -> Result<(), FileOwnerError> {
+    unsupported()
+}
+
+/// Sets owner to files in the directory tree at the given path up to given depth.
+pub fn set_owner_recursive_depth<E: Into<FileOwnerError>>(_path: impl AsRef<Path>, _owner: impl TryInto<Owner, Error = E>, _max_depth: usize) -> Result<(), FileOwnerError> {
+    unsupported()
+}
+
+/// Sets owner to all files inside the directory at the given path, recursively, leaving the directory itself unchanged.
+pub fn set_owner_contents<E: Into<FileOwnerError>>(_path: impl AsRef<Path>, _owner: impl TryInto<Owner, Error = E>, _policy: SymlinkPolicy) -> Result<(), FileOwnerError> {
+    unsupported()
+}
+
+/// Sets owner to all files in the directory tree at the given path that are not already owned by it.
+pub fn set_owner_recursive_if_changed<E: Into<FileOwnerError>>(_path: impl AsRef<Path>, _owner: impl TryInto<Owner, Error = E>) -> Result<usize, FileOwnerError> {
+    unsupported()
+}
+
+/// Finds all files in the directory tree at the given path, including the path itself, not owned by the expected owner.
+pub fn find_not_owned_by<E: Into<FileOwnerError>>(_dir: impl AsRef<Path>, _expected: impl TryInto<Owner, Error = E>) -> Result<Vec<PathBuf>, FileOwnerError> {
+    unsupported()
+}
+
+/// Sets owner to files in the directory tree at the given path for which the predicate returns `true`.
+pub fn set_owner_recursive_filter<E: Into<FileOwnerError>>(_path: impl AsRef<Path>, _owner: impl TryInto<Owner, Error = E>, _predicate: impl FnMut(&Path, &Metadata) -> bool) -> Result<(), FileOwnerError> {
+    unsupported()
+}
+
+/// Sets owner to all files in the directory tree at the given path using a thread pool.
+#[cfg(feature = "rayon")]
+pub fn set_owner_recursive_parallel<E: Into<FileOwnerError>>(_path: impl AsRef<Path>, _owner: impl TryInto<Owner, Error = E>) -> Result<(), FileOwnerError> {
+    unsupported()
+}
+
+/// Sets owner to all files in the directory tree at the given path, continuing past errors.
+pub fn set_owner_recursive_collect<E: Into<FileOwnerError>>(path: impl AsRef<Path>, _owner: impl TryInto<Owner, Error = E>) -> Result<(), Vec<(PathBuf, FileOwnerError)>> {
+    Err(vec![(path.as_ref().to_owned(), FileOwnerError::Unsupported)])
+}
+
+/// Applies owner and group of the `reference` file to all files in the directory tree at `target`, like `chown -R --reference`.
+pub fn chown_recursive_reference(_reference: impl AsRef<Path>, _target: impl AsRef<Path>, _policy: SymlinkPolicy) -> Result<(), FileOwnerError> {
+    unsupported()
+}
+
+/// Sets owner to all files in the directory tree at the given path calling `on_entry` after each entry is changed.
+#[cfg(feature = "walkdir")]
+pub fn set_owner_recursive_with_progress<E: Into<FileOwnerError>>(_path: impl AsRef<Path>, _owner: impl TryInto<Owner, Error = E>, _on_entry: impl FnMut(&Path)) -> Result<(), FileOwnerError> {
+    unsupported()
+}
+
+/// Sets owner to all files matching the given glob pattern.
+#[cfg(feature = "glob")]
+#[allow(clippy::type_complexity)]
+pub fn set_owner_glob<E: Into<FileOwnerError>>(_pattern: &str, _owner: impl TryInto<Owner, Error = E>) -> Result<Vec<(PathBuf, Result<(), FileOwnerError>)>, FileOwnerError> {
+    unsupported()
+}
+
+/// Extension methods for `T: AsRef<Path>`.
+pub trait PathExt {
+    /// Sets owner to file at the given path.
+    fn set_owner<E: Into<FileOwnerError>>(&self, owner: impl TryInto<Owner, Error = E>) -> Result<(), FileOwnerError>;
+
+    /// Sets owner to file at the given path without following symlinks.
+    fn set_owner_no_follow<E: Into<FileOwnerError>>(&self, owner: impl TryInto<Owner, Error = E>) -> Result<(), FileOwnerError>;
+
+    /// Sets group to file at the given path.
+    fn set_group<E: Into<FileOwnerError>>(&self, group: impl TryInto<Group, Error = E>) -> Result<(), FileOwnerError>;
+
+    /// Sets group to file at the given path without following symlinks.
+    fn set_group_no_follow<E: Into<FileOwnerError>>(&self, group: impl TryInto<Group, Error = E>) -> Result<(), FileOwnerError>;
+
+    /// Sets owner and group to file at the given path.
+    fn set_owner_group<E1: Into<FileOwnerError>, E2: Into<FileOwnerError>>(&self, owner: impl TryInto<Owner, Error = E1>, group: impl TryInto<Group, Error = E2>) -> Result<(), FileOwnerError>;
+
+    /// Sets owner and group to file at the given path without following symlinks.
+    fn set_owner_group_no_follow<E1: Into<FileOwnerError>, E2: Into<FileOwnerError>>(&self, owner: impl TryInto<Owner, Error = E1>, group: impl TryInto<Group, Error = E2>) -> Result<(), FileOwnerError>;
+
+    /// Gets owner of a file at the given path.
+    fn owner(&self) -> Result<Owner, FileOwnerError>;
+
+    /// Gets owner of a file at the given path without following symlinks.
+    fn owner_no_follow(&self) -> Result<Owner, FileOwnerError>;
+
+    /// Gets group of a file at the given path.
+    fn group(&self) -> Result<Group, FileOwnerError>;
+
+    /// Gets group of a file at the given path without following symlinks.
+    fn group_no_follow(&self) -> Result<Group, FileOwnerError>;
+
+    /// Gets owner and group of a file at the given path.
+    fn owner_group(&self) -> Result<(Owner, Group), FileOwnerError>;
+
+    /// Gets owner and group of a file at the given path without following symlinks.
+    fn owner_group_no_follow(&self) -> Result<(Owner, Group), FileOwnerError>;
+
+    /// Gets ownership of a file at the given path.
+    fn ownership(&self) -> Result<Ownership, FileOwnerError>;
+
+    /// Gets owner, group and permission bits of a file at the given path.
+    fn ownership_mode(&self) -> Result<(Owner, Group, u32), FileOwnerError>;
+
+    /// Sets ownership to file at the given path.
+    fn set_ownership(&self, ownership: &Ownership) -> Result<(), FileOwnerError>;
+
+    /// Sets owner and/or group to file at the given path as specified.
+    fn set_spec(&self, spec: &OwnerGroupSpec) -> Result<(), FileOwnerError>;
+
+    /// Checks whether file at the given path is owned by the given user.
+    fn is_owned_by<E: Into<FileOwnerError>>(&self, owner: impl TryInto<Owner, Error = E>) -> Result<bool, FileOwnerError>;
+
+    /// Checks whether file at the given path belongs to the given group.
+    fn is_group<E: Into<FileOwnerError>>(&self, group: impl TryInto<Group, Error = E>) -> Result<bool, FileOwnerError>;
+}
+
+impl<T: AsRef<Path>> PathExt for T {
+    fn set_owner<E: Into<FileOwnerError>>(&self, owner: impl TryInto<Owner, Error = E>) -> Result<(), FileOwnerError> {
+        set_owner(self, owner)
+    }
+
+    fn set_owner_no_follow<E: Into<FileOwnerError>>(&self, owner: impl TryInto<Owner, Error = E>) -> Result<(), FileOwnerError> {
+        set_owner_no_follow(self, owner)
+    }
+
+    fn set_group<E: Into<FileOwnerError>>(&self, group: impl TryInto<Group, Error = E>) -> Result<(), FileOwnerError> {
+        set_group(self, group)
+    }
+
+    fn set_group_no_follow<E: Into<FileOwnerError>>(&self, group: impl TryInto<Group, Error = E>) -> Result<(), FileOwnerError> {
+        set_group_no_follow(self, group)
+    }
+
+    fn set_owner_group<E1: Into<FileOwnerError>, E2: Into<FileOwnerError>>(&self, owner: impl TryInto<Owner, Error = E1>, group: impl TryInto<Group, Error = E2>) -> Result<(), FileOwnerError> {
+        set_owner_group(self, owner, group)
+    }
+
+    fn set_owner_group_no_follow<E1: Into<FileOwnerError>, E2: Into<FileOwnerError>>(&self, owner: impl TryInto<Owner, Error = E1>, group: impl TryInto<Group, Error = E2>) -> Result<(), FileOwnerError> {
+        set_owner_group_no_follow(self, owner, group)
+    }
+
+    fn owner(&self) -> Result<Owner, FileOwnerError> {
+        owner(self)
+    }
+
+    fn owner_no_follow(&self) -> Result<Owner, FileOwnerError> {
+        owner_no_follow(self)
+    }
+
+    fn group(&self) -> Result<Group, FileOwnerError> {
+        group(self)
+    }
+
+    fn group_no_follow(&self) -> Result<Group, FileOwnerError> {
+        group_no_follow(self)
+    }
+
+    fn owner_group(&self) -> Result<(Owner, Group), FileOwnerError> {
+        owner_group(self)
+    }
+
+    fn owner_group_no_follow(&self) -> Result<(Owner, Group), FileOwnerError> {
+        owner_group_no_follow(self)
+    }
+
+    fn ownership(&self) -> Result<Ownership, FileOwnerError> {
+        ownership(self)
+    }
+
+    fn ownership_mode(&self) -> Result<(Owner, Group, u32), FileOwnerError> {
+        ownership_mode(self)
+    }
+
+    fn set_ownership(&self, ownership: &Ownership) -> Result<(), FileOwnerError> {
+        set_ownership(self, ownership)
+    }
+
+    fn set_spec(&self, spec: &OwnerGroupSpec) -> Result<(), FileOwnerError> {
+        set_spec(self, spec)
+    }
+
+    fn is_owned_by<E: Into<FileOwnerError>>(&self, owner: impl TryInto<Owner, Error = E>) -> Result<bool, FileOwnerError> {
+        is_owned_by(self, owner)
+    }
+
+    fn is_group<E: Into<FileOwnerError>>(&self, group: impl TryInto<Group, Error = E>) -> Result<bool, FileOwnerError> {
+        is_group(self, group)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    #[test]
+    fn test_unsupported() {
+        assert!(matches!("foo".set_owner(0), Err(FileOwnerError::Unsupported)));
+        assert!(matches!(owner("foo"), Err(FileOwnerError::Unsupported)));
+        assert!(matches!(Owner::from_name("nobody"), Err(FileOwnerError::Unsupported)));
+        assert!(matches!("nobody:0".parse::<OwnerGroupSpec>(), Err(FileOwnerError::Unsupported)));
+        assert_eq!("0:0".parse::<OwnerGroupSpec>().unwrap(), OwnerGroupSpec { owner: Some(Owner::from_uid(0)), group: Some(Group::from_gid(0)) });
+        assert_eq!(Owner::from_uid(0).to_string(), "0");
+        assert_eq!(io::Error::from(FileOwnerError::Unsupported).kind(), io::ErrorKind::Unsupported);
+    }
+}
@@ -0,0 +1,2717 @@
+use nix::unistd::{chown, fchown, fchownat, FchownatFlags};
+use nix::sys::stat::fstat;
+use nix::errno::Errno;
+use nix::libc::{uid_t, gid_t};
+use nix::unistd::{Gid, Uid, Group as NixGroup, User};
+use nix::unistd::{geteuid, getegid, getuid, getgid, seteuid};
+use std::path::{Path, PathBuf};
+use std::cmp::Ordering;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use std::convert::{TryFrom, TryInto};
+use std::fs::{self, File};
+use std::io;
+use std::os::unix::fs::MetadataExt;
+use std::os::unix::io::{AsRawFd, RawFd};
+use crate::error::path_io_error;
+use crate::FileOwnerError;
+use crate::parse::{parse_group, parse_owner, parse_spec};
+
+mod db;
+mod recursive;
+pub use recursive::{SymlinkPolicy, set_owner_recursive, set_owner_recursive_with, set_owner_recursive_depth, set_owner_recursive_filter, set_owner_recursive_collect, set_owner_group_recursive, chown_recursive_reference, set_owner_contents, set_owner_recursive_if_changed, find_not_owned_by};
+#[cfg(feature = "rayon")]
+pub use recursive::set_owner_recursive_parallel;
+#[cfg(feature = "walkdir")]
+pub use recursive::set_owner_recursive_with_progress;
+#[cfg(feature = "glob")]
+mod glob;
+#[cfg(feature = "glob")]
+pub use self::glob::set_owner_glob;
+
+/// First UID of regular (non-system) user accounts, as conventionally used by Linux distributions.
+pub const SYSTEM_UID_LIMIT: u32 = 1000;
+
+/// Owner of a file.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Owner(Uid);
+
+impl Owner {
+    /// Constructs Owner from UID.
+    ///
+    /// Any `u32` value is accepted, including `u32::MAX`; where `uid_t` is signed the value is reinterpreted bit for bit.
+    /// Prefer [`Owner::from_raw`] for UIDs obtained from system APIs as native `uid_t` so no conversion is involved.
+    #[allow(clippy::unnecessary_cast)] // uid_t is not u32 on all platforms
+    pub fn from_uid(uid: u32) -> Owner {
+        Owner(Uid::from_raw(uid as uid_t))
+    }
+
+    /// Constructs Owner from UID as the platform native `uid_t`.
+    ///
+    /// Every `uid_t` value, including `uid_t::MAX`, is kept as is; [`Owner::as_raw`] returns it unchanged.
+    pub fn from_raw(uid: uid_t) -> Owner {
+        Owner(Uid::from_raw(uid))
+    }
+
+    /// Construct Owner from name.
+    ///
+    /// A name containing a NUL byte is rejected with [`FileOwnerError::InvalidName`].
+    pub fn from_name(user: &str) -> Result<Owner, FileOwnerError> {
+        crate::validate::check_no_nul(user)?;
+        Ok(Owner(User::from_name(user)?.ok_or_else(|| FileOwnerError::UserNotFound(user.to_owned()))?.uid))
+    }
+
+    /// Gets UID.
+    ///
+    /// Negative UIDs on platforms with signed `uid_t` are reinterpreted bit for bit; see [`Owner::try_id`] for a checked conversion.
+    #[allow(clippy::unnecessary_cast)] // uid_t is not u32 on all platforms
+    pub fn id(&self) -> u32 {
+        self.try_id().unwrap_or(self.0.as_raw() as u32)
+    }
+
+    /// Gets UID checking that it fits into `u32` without reinterpretation.
+    #[allow(clippy::useless_conversion)] // uid_t is not u32 on all platforms
+    pub fn try_id(&self) -> Result<u32, FileOwnerError> {
+        u32::try_from(self.0.as_raw()).map_err(|_| FileOwnerError::IdOutOfRange(self.0.as_raw().into()))
+    }
+
+    /// Gets UID as the platform native `uid_t`.
+    pub fn as_raw(&self) -> uid_t {
+        self.0.as_raw()
+    }
+
+    /// Gets UID as `nix` type.
+    ///
+    /// Available on Unix only.
+    pub fn as_uid(&self) -> Uid {
+        self.0
+    }
+
+    /// Constructs Owner from name looked up in the given `passwd(5)` formatted file instead of the system user database.
+    ///
+    /// Useful to resolve names of a chroot or system image, e.g. `Owner::from_name_in("www-data", Path::new("/mnt/image/etc/passwd"))`.
+    /// Empty lines and comments starting with `#` are ignored, as are malformed lines.
+    pub fn from_name_in(user: &str, passwd_path: &Path) -> Result<Owner, FileOwnerError> {
+        Ok(Owner::from_uid(db::find_id_in_file(passwd_path, user)?.ok_or_else(|| FileOwnerError::UserNotFound(user.to_owned()))?))
+    }
+
+    /// Gets name if assigned to UID.
+    pub fn name(&self) -> Result<Option<String>, FileOwnerError> {
+        Ok(User::from_uid(self.0)?.map(|u| u.name))
+    }
+
+    /// Checks whether both have the same user name, regardless of their UIDs.
+    ///
+    /// Useful when the same name is known under different UIDs, e.g. after a remap. Equal UIDs always have equal names.
+    /// Returns [`FileOwnerError::UidNotFound`] if either is not assigned to a user, since unresolvable UIDs cannot be compared by name.
+    pub fn name_eq(&self, other: &Owner) -> Result<bool, FileOwnerError> {
+        let name = |owner: &Owner| owner.name()?.ok_or_else(|| FileOwnerError::UidNotFound(owner.id()));
+        Ok(name(self)? == name(other)?)
+    }
+
+    /// Gets UID together with the name if assigned, e.g. to display both as `name (uid)`.
+    ///
+    /// Only the name needs a user database lookup, which is done once.
+    pub fn resolve(&self) -> Result<(u32, Option<String>), FileOwnerError> {
+        Ok((self.id(), self.name()?))
+    }
+
+    /// Gets name if assigned to UID, or UID as a string otherwise.
+    ///
+    /// Unlike `Display`, errors from the user database lookup are returned instead of falling back to UID.
+    pub fn name_or_id(&self) -> Result<String, FileOwnerError> {
+        name_or_id(self.name(), self.id())
+    }
+
+    /// Checks whether UID is 0 (root).
+    pub fn is_root(&self) -> bool {
+        self.0.is_root()
+    }
+
+    /// Checks whether UID is below [`SYSTEM_UID_LIMIT`] which conventionally marks system accounts.
+    ///
+    /// This includes root. The actual range is configured per system (`UID_MIN` in `login.defs`) and is not consulted.
+    pub fn is_system(&self) -> bool {
+        self.try_id().is_ok_and(|id| id < SYSTEM_UID_LIMIT)
+    }
+
+    /// Checks whether UID is assigned to a user in the user database.
+    pub fn exists(&self) -> Result<bool, FileOwnerError> {
+        Ok(User::from_uid(self.0)?.is_some())
+    }
+
+    /// Gets home directory if UID is assigned to a user.
+    pub fn home_dir(&self) -> Result<Option<PathBuf>, FileOwnerError> {
+        Ok(User::from_uid(self.0)?.map(|u| u.dir))
+    }
+
+    /// Gets login shell if UID is assigned to a user.
+    pub fn shell(&self) -> Result<Option<String>, FileOwnerError> {
+        Ok(User::from_uid(self.0)?.map(|u| u.shell.to_string_lossy().into_owned()))
+    }
+
+    /// Gets GECOS field (usually full name) if UID is assigned to a user.
+    ///
+    /// Returns [`FileOwnerError::Unsupported`] on 32-bit Android which has no GECOS field.
+    pub fn gecos(&self) -> Result<Option<String>, FileOwnerError> {
+        #[cfg(not(all(target_os = "android", target_pointer_width = "32")))]
+        return Ok(User::from_uid(self.0)?.map(|u| u.gecos.to_string_lossy().into_owned()));
+        #[cfg(all(target_os = "android", target_pointer_width = "32"))]
+        return Err(FileOwnerError::Unsupported);
+    }
+
+    /// Gets primary (login) group of the user assigned to UID.
+    ///
+    /// Returns [`FileOwnerError::UidNotFound`] if UID is not assigned to a user.
+    pub fn primary_group(&self) -> Result<Group, FileOwnerError> {
+        Ok(Group(User::from_uid(self.0)?.ok_or_else(|| FileOwnerError::UidNotFound(self.id()))?.gid))
+    }
+
+    /// Gets all users of the user database.
+    ///
+    /// This enumerates the whole database with `getpwent` which is not reentrant.
+    /// Calls within this crate are serialized, but other code using `getpwent` concurrently in the same process will interfere.
+    /// Users sharing a UID are listed once per entry.
+    pub fn all() -> Result<Vec<Owner>, FileOwnerError> {
+        let mut owners = Vec::new();
+        db::for_each_user(|user| owners.push(Owner(user.uid)))?;
+        Ok(owners)
+    }
+
+    /// Gets all groups the user assigned to UID is a member of, including the primary group.
+    ///
+    /// This enumerates the whole group database so it can be slow with large or remote databases.
+    /// Returns [`FileOwnerError::UidNotFound`] if UID is not assigned to a user.
+    pub fn groups(&self) -> Result<Vec<Group>, FileOwnerError> {
+        let user = User::from_uid(self.0)?.ok_or_else(|| FileOwnerError::UidNotFound(self.id()))?;
+        let mut groups = vec![Group(user.gid)];
+        db::for_each_group(|group| {
+            if group.gid != user.gid && group.mem.contains(&user.name) {
+                groups.push(Group(group.gid));
+            }
+        })?;
+        Ok(groups)
+    }
+
+    /// Gets the whole user database entry if UID is assigned to a user.
+    ///
+    /// Each of [`Owner::name`], [`Owner::home_dir`], [`Owner::shell`] and [`Owner::gecos`] does its own lookup;
+    /// use this to get all the fields with a single lookup.
+    ///
+    /// Available on Unix only.
+    pub fn user(&self) -> Result<Option<User>, FileOwnerError> {
+        Ok(User::from_uid(self.0)?)
+    }
+}
+
+impl From<u32> for Owner {
+    fn from(uid: u32) -> Owner {
+        Owner::from_uid(uid)
+    }
+}
+
+impl From<Uid> for Owner {
+    fn from(uid: Uid) -> Owner {
+        Owner(uid)
+    }
+}
+
+/// Gets owner from already fetched file metadata.
+impl From<&fs::Metadata> for Owner {
+    fn from(meta: &fs::Metadata) -> Owner {
+        Owner::from_uid(meta.uid())
+    }
+}
+
+impl<'s> TryFrom<&'s str> for Owner {
+    type Error = FileOwnerError;
+
+    fn try_from(name: &'s str) -> Result<Owner, Self::Error> {
+        Owner::from_name(name)
+    }
+}
+
+/// Parses UID number or user name, like `chown` does.
+///
+/// A string that parses as an integer is always taken as UID.
+impl FromStr for Owner {
+    type Err = FileOwnerError;
+
+    fn from_str(s: &str) -> Result<Owner, Self::Err> {
+        parse_owner(s, Owner::from_name)
+    }
+}
+
+/// Displays user name if assigned to UID, or UID otherwise.
+///
+/// The alternate form (`{:#}`) always displays UID without any user database lookup.
+/// With the `display-numeric` feature enabled the UID is always displayed, so formatting never does a lookup; use `name()` to get the name.
+impl Display for Owner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() || cfg!(feature = "display-numeric") {
+            write!(f, "{}", self.id())
+        } else if let Some(name) = self.name().ok().flatten() {
+            write!(f, "{}", name)
+        } else {
+            write!(f, "{}", self.id())
+        }
+    }
+}
+
+/// Orders by UID numerically, independently of user names.
+impl PartialOrd for Owner {
+    fn partial_cmp(&self, other: &Owner) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Orders by UID numerically, independently of user names.
+impl Ord for Owner {
+    fn cmp(&self, other: &Owner) -> Ordering {
+        self.0.as_raw().cmp(&other.0.as_raw())
+    }
+}
+
+/// Compares the UID with a numeric id.
+impl PartialEq<u32> for Owner {
+    fn eq(&self, other: &u32) -> bool {
+        self.try_id().is_ok_and(|id| id == *other)
+    }
+}
+
+impl PartialEq<Owner> for u32 {
+    fn eq(&self, other: &Owner) -> bool {
+        other == self
+    }
+}
+
+/// Group of a file.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Group(Gid);
+
+impl Group {
+    /// Constructs Group from GUI.
+    ///
+    /// Any `u32` value is accepted, including `u32::MAX`; where `gid_t` is signed the value is reinterpreted bit for bit.
+    /// Prefer [`Group::from_raw`] for GIDs obtained from system APIs as native `gid_t` so no conversion is involved.
+    #[allow(clippy::unnecessary_cast)] // gid_t is not u32 on all platforms
+    pub fn from_gid(gid: u32) -> Group {
+        Group(Gid::from_raw(gid as gid_t))
+    }
+
+    /// Constructs Group from GID as the platform native `gid_t`.
+    ///
+    /// Every `gid_t` value, including `gid_t::MAX`, is kept as is; [`Group::as_raw`] returns it unchanged.
+    pub fn from_raw(gid: gid_t) -> Group {
+        Group(Gid::from_raw(gid))
+    }
+
+    /// Constructs Group from name.
+    ///
+    /// A name containing a NUL byte is rejected with [`FileOwnerError::InvalidName`].
+    pub fn from_name(group: &str) -> Result<Group, FileOwnerError> {
+        crate::validate::check_no_nul(group)?;
+        Ok(Group(NixGroup::from_name(group)?.ok_or_else(|| FileOwnerError::GroupNotFound(group.to_owned()))?.gid))
+    }
+
+    /// Gets GID.
+    ///
+    /// Negative GIDs on platforms with signed `gid_t` are reinterpreted bit for bit; see [`Group::try_id`] for a checked conversion.
+    #[allow(clippy::unnecessary_cast)] // gid_t is not u32 on all platforms
+    pub fn id(&self) -> u32 {
+        self.try_id().unwrap_or(self.0.as_raw() as u32)
+    }
+
+    /// Gets GID checking that it fits into `u32` without reinterpretation.
+    #[allow(clippy::useless_conversion)] // gid_t is not u32 on all platforms
+    pub fn try_id(&self) -> Result<u32, FileOwnerError> {
+        u32::try_from(self.0.as_raw()).map_err(|_| FileOwnerError::IdOutOfRange(self.0.as_raw().into()))
+    }
+
+    /// Gets GID as the platform native `gid_t`.
+    pub fn as_raw(&self) -> gid_t {
+        self.0.as_raw()
+    }
+
+    /// Gets GID as `nix` type.
+    ///
+    /// Available on Unix only.
+    pub fn as_gid(&self) -> Gid {
+        self.0
+    }
+
+    /// Constructs Group from name looked up in the given `group(5)` formatted file instead of the system group database.
+    ///
+    /// Empty lines and comments starting with `#` are ignored, as are malformed lines.
+    pub fn from_name_in(group: &str, group_path: &Path) -> Result<Group, FileOwnerError> {
+        Ok(Group::from_gid(db::find_id_in_file(group_path, group)?.ok_or_else(|| FileOwnerError::GroupNotFound(group.to_owned()))?))
+    }
+
+    /// Gets name if assigned to GID.
+    pub fn name(&self) -> Result<Option<String>, FileOwnerError> {
+        Ok(NixGroup::from_gid(self.0)?.map(|u| u.name))
+    }
+
+    /// Checks whether both have the same group name, regardless of their GIDs.
+    ///
+    /// Useful when the same name is known under different GIDs, e.g. after a remap. Equal GIDs always have equal names.
+    /// Returns [`FileOwnerError::GidNotFound`] if either is not assigned to a group, since unresolvable GIDs cannot be compared by name.
+    pub fn name_eq(&self, other: &Group) -> Result<bool, FileOwnerError> {
+        let name = |group: &Group| group.name()?.ok_or_else(|| FileOwnerError::GidNotFound(group.id()));
+        Ok(name(self)? == name(other)?)
+    }
+
+    /// Gets GID together with the name if assigned, e.g. to display both as `name (gid)`.
+    ///
+    /// Only the name needs a group database lookup, which is done once.
+    pub fn resolve(&self) -> Result<(u32, Option<String>), FileOwnerError> {
+        Ok((self.id(), self.name()?))
+    }
+
+    /// Gets name if assigned to GID, or GID as a string otherwise.
+    ///
+    /// Unlike `Display`, errors from the group database lookup are returned instead of falling back to GID.
+    pub fn name_or_id(&self) -> Result<String, FileOwnerError> {
+        name_or_id(self.name(), self.id())
+    }
+
+    /// Checks whether GID is 0 (root group).
+    pub fn is_root(&self) -> bool {
+        self.0.as_raw() == 0
+    }
+
+    /// Checks whether GID is assigned to a group in the group database.
+    pub fn exists(&self) -> Result<bool, FileOwnerError> {
+        Ok(NixGroup::from_gid(self.0)?.is_some())
+    }
+
+    /// Gets all groups of the group database.
+    ///
+    /// This enumerates the whole database with `getgrent` which is not reentrant.
+    /// Calls within this crate are serialized, but other code using `getgrent` concurrently in the same process will interfere.
+    /// Groups sharing a GID are listed once per entry.
+    pub fn all() -> Result<Vec<Group>, FileOwnerError> {
+        let mut groups = Vec::new();
+        db::for_each_group(|group| groups.push(Group(group.gid)))?;
+        Ok(groups)
+    }
+
+    /// Gets names of all group database entries with this GID, in database order.
+    ///
+    /// [`Group::name`] uses `getgrgid` which returns only the first matching entry; this lists all of them to diagnose ambiguous GID to name mappings.
+    /// Enumerates the whole database with `getgrent`, see [`Group::all`]. Returns an empty list if GID is not assigned to any group.
+    pub fn names_for_gid(&self) -> Result<Vec<String>, FileOwnerError> {
+        let mut names = Vec::new();
+        db::for_each_group(|group| {
+            if group.gid == self.0 {
+                names.push(group.name);
+            }
+        })?;
+        Ok(names)
+    }
+
+    /// Gets names of all entries with this GID in the given `group(5)` formatted file instead of the system group database.
+    ///
+    /// Parsed like [`Group::from_name_in`].
+    pub fn names_for_gid_in(&self, group_path: &Path) -> Result<Vec<String>, FileOwnerError> {
+        db::find_names_in_file(group_path, self.try_id()?)
+    }
+
+    /// Checks whether the user assigned to the given UID is a member of the group assigned to GID.
+    ///
+    /// Membership comes from two sources: the primary group of the user from the user database,
+    /// and the supplementary member list of the group from the group database (see [`Group::members`]).
+    /// A GID without a group entry can only match as a primary group.
+    /// Returns [`FileOwnerError::UidNotFound`] if UID is not assigned to a user.
+    pub fn contains(&self, owner: Owner) -> Result<bool, FileOwnerError> {
+        let user = User::from_uid(owner.0)?.ok_or_else(|| FileOwnerError::UidNotFound(owner.id()))?;
+        if user.gid == self.0 {
+            return Ok(true);
+        }
+        Ok(NixGroup::from_gid(self.0)?.is_some_and(|group| group.mem.contains(&user.name)))
+    }
+
+    /// Gets names of the users listed as members of the group assigned to GID.
+    ///
+    /// These are the explicit supplementary members from the group database; users that have this group as their primary group are not included.
+    /// Returns [`FileOwnerError::GidNotFound`] if GID is not assigned to a group.
+    pub fn members(&self) -> Result<Vec<String>, FileOwnerError> {
+        Ok(NixGroup::from_gid(self.0)?.ok_or_else(|| FileOwnerError::GidNotFound(self.id()))?.mem)
+    }
+}
+
+impl From<u32> for Group {
+    fn from(gid: u32) -> Group {
+        Group::from_gid(gid)
+    }
+}
+
+impl From<Gid> for Group {
+    fn from(gid: Gid) -> Group {
+        Group(gid)
+    }
+}
+
+/// Gets group from already fetched file metadata.
+impl From<&fs::Metadata> for Group {
+    fn from(meta: &fs::Metadata) -> Group {
+        Group::from_gid(meta.gid())
+    }
+}
+
+impl<'s> TryFrom<&'s str> for Group {
+    type Error = FileOwnerError;
+
+    fn try_from(name: &'s str) -> Result<Group, Self::Error> {
+        Group::from_name(name)
+    }
+}
+
+/// Parses GID number or group name, like `chown` does.
+///
+/// A string that parses as an integer is always taken as GID, so a group whose name consists only of digits cannot be resolved by name this way;
+/// use [`Group::from_name`] for that.
+impl FromStr for Group {
+    type Err = FileOwnerError;
+
+    fn from_str(s: &str) -> Result<Group, Self::Err> {
+        parse_group(s, Group::from_name)
+    }
+}
+
+/// Displays group name if assigned to GID, or GID otherwise.
+///
+/// The alternate form (`{:#}`) always displays GID without any group database lookup.
+/// With the `display-numeric` feature enabled the GID is always displayed, so formatting never does a lookup; use `name()` to get the name.
+impl Display for Group {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() || cfg!(feature = "display-numeric") {
+            write!(f, "{}", self.id())
+        } else if let Some(name) = self.name().ok().flatten() {
+            write!(f, "{}", name)
+        } else {
+            write!(f, "{}", self.id())
+        }
+    }
+}
+
+/// Compares the GID with a numeric id.
+impl PartialEq<u32> for Group {
+    fn eq(&self, other: &u32) -> bool {
+        self.try_id().is_ok_and(|id| id == *other)
+    }
+}
+
+impl PartialEq<Group> for u32 {
+    fn eq(&self, other: &Group) -> bool {
+        other == self
+    }
+}
+
+/// Orders by GID numerically, independently of group names.
+impl PartialOrd for Group {
+    fn partial_cmp(&self, other: &Group) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Orders by GID numerically, independently of group names.
+impl Ord for Group {
+    fn cmp(&self, other: &Group) -> Ordering {
+        self.0.as_raw().cmp(&other.0.as_raw())
+    }
+}
+
+fn name_or_id(name: Result<Option<String>, FileOwnerError>, id: u32) -> Result<String, FileOwnerError> {
+    Ok(name?.unwrap_or_else(|| id.to_string()))
+}
+
+/// Owner and group of a file.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct Ownership {
+    pub owner: Owner,
+    pub group: Group,
+}
+
+impl Ownership {
+    /// Constructs Ownership from Owner and Group.
+    pub fn new(owner: Owner, group: Group) -> Ownership {
+        Ownership { owner, group }
+    }
+
+    /// Constructs Ownership from UID and GID.
+    pub fn from_ids(uid: u32, gid: u32) -> Ownership {
+        Ownership::new(Owner::from_uid(uid), Group::from_gid(gid))
+    }
+
+    /// Constructs Ownership from already fetched file metadata.
+    ///
+    /// Available on Unix only.
+    pub fn from_metadata(meta: &fs::Metadata) -> Ownership {
+        Ownership::new(meta.into(), meta.into())
+    }
+
+    /// Constructs Ownership from user and group names.
+    pub fn from_names(user: &str, group: &str) -> Result<Ownership, FileOwnerError> {
+        Ok(Ownership::new(Owner::from_name(user)?, Group::from_name(group)?))
+    }
+
+    /// Compares with other Ownership by UID and GID.
+    pub fn diff(&self, other: &Ownership) -> OwnershipDiff {
+        match (self.owner == other.owner, self.group == other.group) {
+            (true, true) => OwnershipDiff::Same,
+            (false, true) => OwnershipDiff::Owner,
+            (true, false) => OwnershipDiff::Group,
+            (false, false) => OwnershipDiff::Both,
+        }
+    }
+}
+
+impl From<(Owner, Group)> for Ownership {
+    fn from((owner, group): (Owner, Group)) -> Ownership {
+        Ownership::new(owner, group)
+    }
+}
+
+/// Which parts of two [`Ownership`]s differ.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum OwnershipDiff {
+    /// Both owner and group are the same.
+    Same,
+    /// Only the owner differs.
+    Owner,
+    /// Only the group differs.
+    Group,
+    /// Both owner and group differ.
+    Both,
+}
+
+impl OwnershipDiff {
+    /// Checks whether owner and group are the same.
+    pub fn is_same(&self) -> bool {
+        *self == OwnershipDiff::Same
+    }
+}
+
+/// Owner and group specification as accepted by `chown`, with either part optional.
+///
+/// Parses `"user:group"`, `"user"` (group unchanged), `":group"` (owner unchanged) and `"user:"` (group set to the login group of the user).
+/// Each part can be a name or a numeric id as with [`Owner`] and [`Group`] `FromStr` implementations.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct OwnerGroupSpec {
+    pub owner: Option<Owner>,
+    pub group: Option<Group>,
+}
+
+impl FromStr for OwnerGroupSpec {
+    type Err = FileOwnerError;
+
+    fn from_str(s: &str) -> Result<OwnerGroupSpec, Self::Err> {
+        parse_spec(s, Owner::from_name, Group::from_name)
+    }
+}
+
+/// Gets owner corresponding to the effective UID of the running process.
+///
+/// Available on Unix only.
+pub fn current_owner() -> Owner {
+    Owner(geteuid())
+}
+
+/// Gets group corresponding to the effective GID of the running process.
+///
+/// Available on Unix only.
+pub fn current_group() -> Group {
+    Group(getegid())
+}
+
+/// Gets owner corresponding to the real UID of the running process.
+///
+/// Available on Unix only.
+pub fn real_owner() -> Owner {
+    Owner(getuid())
+}
+
+/// Gets group corresponding to the real GID of the running process.
+///
+/// Available on Unix only.
+pub fn real_group() -> Group {
+    Group(getgid())
+}
+
+/// Checks whether the running process is likely allowed to change owner of arbitrary files.
+///
+/// Returns `true` if effective UID is root or, on Linux, if the process has `CAP_CHOWN` in its effective capability set.
+/// This is a best-effort check only: capabilities may be dropped later, and security modules, read-only or network file systems and user namespaces may still reject a `chown`, so errors must be handled regardless.
+pub fn can_change_owner() -> bool {
+    geteuid().is_root() || has_cap_chown()
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn has_cap_chown() -> bool {
+    /// Bit of `CAP_CHOWN` in capability sets.
+    const CAP_CHOWN: u64 = 1 << 0;
+
+    fs::read_to_string("/proc/self/status").ok()
+        .and_then(|status| status.lines().find_map(|line| line.strip_prefix("CapEff:").map(|caps| u64::from_str_radix(caps.trim(), 16))))
+        .and_then(Result::ok)
+        .is_some_and(|caps| caps & CAP_CHOWN != 0)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn has_cap_chown() -> bool {
+    false
+}
+
+/// Runs `f` with the effective UID temporarily set to 0 (root) and restores the previous effective UID afterwards.
+///
+/// Meant for daemons that started as root and dropped privileges with `seteuid`, keeping 0 as the real or saved set-user-ID.
+/// Re-elevation works only in that case; otherwise `seteuid(0)` fails and `EPERM` is returned as [`FileOwnerError::NixError`] without calling `f`.
+/// The effective UID is restored even if `f` panics. If restoring fails after `f` returned, that error is returned instead of the result of `f`.
+///
+/// The effective UID is a process wide attribute, so other threads run as root too while `f` runs.
+pub fn with_privileges<T>(f: impl FnOnce() -> Result<T, FileOwnerError>) -> Result<T, FileOwnerError> {
+    /// Restores effective UID when `f` panics.
+    struct Restore(Uid);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let _ = seteuid(self.0);
+        }
+    }
+
+    let saved = geteuid();
+    seteuid(Uid::from_raw(0))?;
+    let restore = Restore(saved);
+    let result = f();
+    std::mem::forget(restore);
+    seteuid(saved)?;
+    result
+}
+
+/// Maps a failed `chown` on `path`: EPERM/EACCES to PermissionDenied, ENOENT to PathNotFound and any other errno to NixError.
+fn chown_error(path: &Path, err: nix::Error) -> FileOwnerError {
+    match err {
+        Errno::EPERM | Errno::EACCES => FileOwnerError::PermissionDenied { path: path.to_owned() },
+        Errno::ENOENT => FileOwnerError::PathNotFound(path.to_owned()),
+        err => FileOwnerError::NixError(err),
+    }
+}
+
+/// Number of attempts made for a call interrupted by a signal before `EINTR` is returned.
+const EINTR_ATTEMPTS: usize = 8;
+
+fn retry_eintr<T>(mut f: impl FnMut() -> nix::Result<T>) -> nix::Result<T> {
+    let mut attempts = 1;
+    loop {
+        match f() {
+            Err(Errno::EINTR) if attempts < EINTR_ATTEMPTS => attempts += 1,
+            result => return result,
+        }
+    }
+}
+
+fn chown_ids(path: &Path, uid: Option<Uid>, gid: Option<Gid>, follow: bool) -> Result<(), FileOwnerError> {
+    retry_eintr(|| if follow {
+        chown(path, uid, gid)
+    } else {
+        fchownat(None, path, uid, gid, FchownatFlags::NoFollowSymlink)
+    }).map_err(|err| chown_error(path, err))
+}
+
+/// Sets owner to file at the given path.
+pub fn set_owner<E: Into<FileOwnerError>>(path: impl AsRef<Path>, owner: impl TryInto<Owner, Error = E>) -> Result<(), FileOwnerError> {
+    set_owner_with(path, owner, true)
+}
+
+/// Sets owner to file at the given path without following symlinks.
+///
+/// If the path is a symbolic link the ownership of the link itself is changed (`lchown` semantics).
+pub fn set_owner_no_follow<E: Into<FileOwnerError>>(path: impl AsRef<Path>, owner: impl TryInto<Owner, Error = E>) -> Result<(), FileOwnerError> {
+    set_owner_with(path, owner, false)
+}
+
+/// Sets owner to file at the given path, following symlinks or not.
+///
+/// With `follow = true` this uses `chown` semantics and with `follow = false` it uses `lchown` semantics.
+pub fn set_owner_with<E: Into<FileOwnerError>>(path: impl AsRef<Path>, owner: impl TryInto<Owner, Error = E>, follow: bool) -> Result<(), FileOwnerError> {
+    chown_ids(path.as_ref(), Some(owner.try_into().map_err(Into::into)?.0), None, follow)
+}
+
+/// Sets group to file at the given path.
+pub fn set_group<E: Into<FileOwnerError>>(path: impl AsRef<Path>, group: impl TryInto<Group, Error = E>) -> Result<(), FileOwnerError> {
+    set_group_with(path, group, true)
+}
+
+/// Sets group to file at the given path without following symlinks.
+///
+/// If the path is a symbolic link the group of the link itself is changed (`lchown` semantics).
+pub fn set_group_no_follow<E: Into<FileOwnerError>>(path: impl AsRef<Path>, group: impl TryInto<Group, Error = E>) -> Result<(), FileOwnerError> {
+    set_group_with(path, group, false)
+}
+
+/// Sets group to file at the given path, following symlinks or not.
+///
+/// With `follow = true` this uses `chown` semantics and with `follow = false` it uses `lchown` semantics.
+pub fn set_group_with<E: Into<FileOwnerError>>(path: impl AsRef<Path>, group: impl TryInto<Group, Error = E>, follow: bool) -> Result<(), FileOwnerError> {
+    chown_ids(path.as_ref(), None, Some(group.try_into().map_err(Into::into)?.0), follow)
+}
+
+/// Sets owner and group to file at the given path.
+pub fn set_owner_group<E1: Into<FileOwnerError>, E2: Into<FileOwnerError>>(path: impl AsRef<Path>, owner: impl TryInto<Owner, Error = E1>, group: impl TryInto<Group, Error = E2>) -> Result<(), FileOwnerError> {
+    set_owner_group_with(path, owner, group, true)
+}
+
+/// Sets owner and group to file at the given path without following symlinks.
+///
+/// Both are applied with a single `lchown` call to the symbolic link itself.
+pub fn set_owner_group_no_follow<E1: Into<FileOwnerError>, E2: Into<FileOwnerError>>(path: impl AsRef<Path>, owner: impl TryInto<Owner, Error = E1>, group: impl TryInto<Group, Error = E2>) -> Result<(), FileOwnerError> {
+    set_owner_group_with(path, owner, group, false)
+}
+
+/// Sets owner and group to file at the given path, following symlinks or not.
+///
+/// With `follow = true` this uses `chown` semantics and with `follow = false` it uses `lchown` semantics.
+pub fn set_owner_group_with<E1: Into<FileOwnerError>, E2: Into<FileOwnerError>>(path: impl AsRef<Path>, owner: impl TryInto<Owner, Error = E1>, group: impl TryInto<Group, Error = E2>, follow: bool) -> Result<(), FileOwnerError> {
+    chown_ids(path.as_ref(), Some(owner.try_into().map_err(Into::into)?.0), Some(group.try_into().map_err(Into::into)?.0), follow)
+}
+
+/// Sets owner to file referred to by the given open file descriptor.
+///
+/// Available on Unix only.
+pub fn set_owner_fd<E: Into<FileOwnerError>>(fd: RawFd, owner: impl TryInto<Owner, Error = E>) -> Result<(), FileOwnerError> {
+    let uid = owner.try_into().map_err(Into::into)?.0;
+    Ok(retry_eintr(|| fchown(fd, Some(uid), None))?)
+}
+
+/// Sets owner to the inode referred to by the given file descriptor using `fchownat` with `AT_EMPTY_PATH`.
+///
+/// Unlike [`set_owner_fd`] this works with `O_PATH` file descriptors, which is the only way to change ownership of some inodes reached that way.
+/// Returns [`FileOwnerError::Unsupported`] on platforms other than Linux and Android which lack `AT_EMPTY_PATH`.
+///
+/// Available on Unix only.
+pub fn set_owner_fd_path<E: Into<FileOwnerError>>(fd: RawFd, owner: impl TryInto<Owner, Error = E>) -> Result<(), FileOwnerError> {
+    let uid = owner.try_into().map_err(Into::into)?.0;
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        // A `gid_t` of -1 leaves the group unchanged.
+        Ok(retry_eintr(|| Errno::result(unsafe { nix::libc::fchownat(fd, b"\0".as_ptr().cast(), uid.as_raw(), gid_t::MAX, nix::libc::AT_EMPTY_PATH) }).map(drop))?)
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    {
+        let _ = (fd, uid);
+        Err(FileOwnerError::Unsupported)
+    }
+}
+
+/// Sets owner to the given open file.
+pub fn set_owner_file<E: Into<FileOwnerError>>(file: &File, owner: impl TryInto<Owner, Error = E>) -> Result<(), FileOwnerError> {
+    set_owner_fd(file.as_raw_fd(), owner)
+}
+
+/// Sets owner to all files at the given paths resolving the owner once.
+///
+/// All paths are processed even if some fail. Returns the number of changed paths or all the per-path errors.
+/// If the owner cannot be resolved a single error with an empty path is returned.
+pub fn set_owner_many<E: Into<FileOwnerError>>(paths: impl IntoIterator<Item = impl AsRef<Path>>, owner: impl TryInto<Owner, Error = E>) -> Result<usize, Vec<(PathBuf, FileOwnerError)>> {
+    let owner = owner.try_into().map_err(|err| vec![(PathBuf::new(), err.into())])?;
+    let mut changed = 0;
+    let mut errors = Vec::new();
+
+    for path in paths {
+        match set_owner(&path, owner) {
+            Ok(()) => changed += 1,
+            Err(err) => errors.push((path.as_ref().to_owned(), err)),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(changed)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Sets owner to file at the given path relative to the directory referred to by `dirfd`.
+///
+/// With `follow = false` the `AT_SYMLINK_NOFOLLOW` flag is used so a symbolic link itself is changed.
+/// Absolute paths ignore `dirfd`.
+///
+/// Available on Unix only.
+pub fn set_owner_at<E: Into<FileOwnerError>>(dirfd: RawFd, path: impl AsRef<Path>, owner: impl TryInto<Owner, Error = E>, follow: bool) -> Result<(), FileOwnerError> {
+    let flag = if follow { FchownatFlags::FollowSymlink } else { FchownatFlags::NoFollowSymlink };
+    let path = path.as_ref();
+    let uid = owner.try_into().map_err(Into::into)?.0;
+    retry_eintr(|| fchownat(Some(dirfd), path, Some(uid), None, flag)).map_err(|err| chown_error(path, err))
+}
+
+/// Gets owner of a file referred to by the given open file descriptor.
+///
+/// Available on Unix only.
+pub fn owner_fd(fd: RawFd) -> Result<Owner, FileOwnerError> {
+    Ok(Owner(Uid::from_raw(fstat(fd)?.st_uid)))
+}
+
+/// Gets group of a file referred to by the given open file descriptor.
+///
+/// Available on Unix only.
+pub fn group_fd(fd: RawFd) -> Result<Group, FileOwnerError> {
+    Ok(Group(Gid::from_raw(fstat(fd)?.st_gid)))
+}
+
+/// Gets owner and group of a file referred to by the given open file descriptor.
+///
+/// Available on Unix only.
+pub fn owner_group_fd(fd: RawFd) -> Result<(Owner, Group), FileOwnerError> {
+    let stat = fstat(fd)?;
+    Ok((Owner(Uid::from_raw(stat.st_uid)), Group(Gid::from_raw(stat.st_gid))))
+}
+
+/// Gets owner of the given open file.
+///
+/// This works for files that have no path, like unlinked or `O_TMPFILE` files.
+pub fn owner_file(file: &File) -> Result<Owner, FileOwnerError> {
+    owner_fd(file.as_raw_fd())
+}
+
+/// Gets group of the given open file.
+pub fn group_file(file: &File) -> Result<Group, FileOwnerError> {
+    group_fd(file.as_raw_fd())
+}
+
+/// Gets owner and group of the given open file.
+pub fn owner_group_file(file: &File) -> Result<(Owner, Group), FileOwnerError> {
+    owner_group_fd(file.as_raw_fd())
+}
+
+fn metadata(path: &Path, follow: bool) -> Result<fs::Metadata, FileOwnerError> {
+    if follow { fs::metadata(path) } else { fs::symlink_metadata(path) }.map_err(|source| path_io_error(path, source))
+}
+
+/// Gets owner of a file at the given path.
+///
+/// Special files (devices, FIFOs and sockets) are only `stat`ed, never opened, so the owner of the node itself is returned.
+pub fn owner(path: impl AsRef<Path>) -> Result<Owner, FileOwnerError> {
+    Ok(Owner::from_uid(metadata(path.as_ref(), true)?.uid()))
+}
+
+/// Gets owner of a file at the given path without following symlinks.
+///
+/// If the path is a symbolic link the owner of the link itself is returned, even if its target does not exist.
+pub fn owner_no_follow(path: impl AsRef<Path>) -> Result<Owner, FileOwnerError> {
+    Ok(Owner::from_uid(metadata(path.as_ref(), false)?.uid()))
+}
+
+/// Gets group of a file at the given path.
+pub fn group(path: impl AsRef<Path>) -> Result<Group, FileOwnerError> {
+    Ok(Group::from_gid(metadata(path.as_ref(), true)?.gid()))
+}
+
+/// Gets group of a file at the given path without following symlinks.
+///
+/// If the path is a symbolic link the group of the link itself is returned, even if its target does not exist.
+pub fn group_no_follow(path: impl AsRef<Path>) -> Result<Group, FileOwnerError> {
+    Ok(Group::from_gid(metadata(path.as_ref(), false)?.gid()))
+}
+
+/// Gets owner and group of a file at the given path.
+///
+/// As with [`owner`], special files are only `stat`ed and the ownership of the node itself is returned.
+pub fn owner_group(path: impl AsRef<Path>) -> Result<(Owner, Group), FileOwnerError> {
+    let meta = metadata(path.as_ref(), true)?;
+    Ok((Owner::from_uid(meta.uid()), Group::from_gid(meta.gid())))
+}
+
+/// Gets owner and group of each file at the given paths, in the same order.
+///
+/// Each path is `stat`ed once and failures are reported per path. No names are looked up;
+/// to display names of many owners and groups share a [`UserCache`] and [`GroupCache`] so each id is resolved only once.
+pub fn owner_group_many<P: AsRef<Path>>(paths: impl IntoIterator<Item = P>) -> Vec<Result<(Owner, Group), FileOwnerError>> {
+    paths.into_iter().map(owner_group).collect()
+}
+
+/// Gets owner and group of a file at the given path without following symlinks.
+///
+/// Both are taken from a single `lstat` call.
+pub fn owner_group_no_follow(path: impl AsRef<Path>) -> Result<(Owner, Group), FileOwnerError> {
+    let meta = metadata(path.as_ref(), false)?;
+    Ok((Owner::from_uid(meta.uid()), Group::from_gid(meta.gid())))
+}
+
+/// Gets ownership of a file at the given path.
+pub fn ownership(path: impl AsRef<Path>) -> Result<Ownership, FileOwnerError> {
+    Ok(owner_group(path)?.into())
+}
+
+/// Gets owner, group and permission bits (`mode & 0o7777`) of a file at the given path with a single `stat` call.
+pub fn ownership_mode(path: impl AsRef<Path>) -> Result<(Owner, Group, u32), FileOwnerError> {
+    let meta = metadata(path.as_ref(), true)?;
+    Ok((Owner::from(&meta), Group::from(&meta), meta.mode() & 0o7777))
+}
+
+/// Checks whether file at the given path is owned by the given user, following symlinks.
+///
+/// A user name is resolved once and compared with the UID of the file, so `"www-data"` can be passed directly.
+/// Returns [`FileOwnerError::UserNotFound`] if the name does not exist.
+pub fn is_owned_by<E: Into<FileOwnerError>>(path: impl AsRef<Path>, owner: impl TryInto<Owner, Error = E>) -> Result<bool, FileOwnerError> {
+    let owner = owner.try_into().map_err(Into::into)?;
+    Ok(crate::owner(path)? == owner)
+}
+
+/// Checks whether file at the given path belongs to the given group, following symlinks.
+///
+/// A group name is resolved once and compared with the GID of the file.
+/// Returns [`FileOwnerError::GroupNotFound`] if the name does not exist.
+pub fn is_group<E: Into<FileOwnerError>>(path: impl AsRef<Path>, group: impl TryInto<Group, Error = E>) -> Result<bool, FileOwnerError> {
+    let group = group.try_into().map_err(Into::into)?;
+    Ok(crate::group(path)? == group)
+}
+
+/// Sets ownership to file at the given path.
+///
+/// Owner and group are applied with a single `chown` call.
+pub fn set_ownership(path: impl AsRef<Path>, ownership: &Ownership) -> Result<(), FileOwnerError> {
+    set_owner_group(path, ownership.owner, ownership.group)
+}
+
+/// Copies owner and group of file at `src` path to file at `dst` path.
+///
+/// Like `chown --reference`, the `src` is read with a single `stat` call and both ids are applied with a single `chown` call.
+/// Failure to read `src` is reported with the `src` path as [`FileOwnerError::PathNotFound`] if it does not exist or [`FileOwnerError::Io`] otherwise.
+/// Failure to change `dst` is reported with the `dst` path as [`FileOwnerError::PermissionDenied`] or [`FileOwnerError::PathNotFound`], like for [`set_owner_group`].
+pub fn copy_ownership(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> Result<(), FileOwnerError> {
+    copy_ownership_with(src.as_ref(), dst.as_ref(), true)
+}
+
+/// Copies owner and group of file at `src` path to file at `dst` path without following symlinks on either path.
+pub fn copy_ownership_no_follow(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> Result<(), FileOwnerError> {
+    copy_ownership_with(src.as_ref(), dst.as_ref(), false)
+}
+
+fn copy_ownership_with(src: &Path, dst: &Path, follow: bool) -> Result<(), FileOwnerError> {
+    let (owner, group) = if follow { owner_group(src)? } else { owner_group_no_follow(src)? };
+    chown_ids(dst, Some(owner.0), Some(group.0), follow)
+}
+
+/// Applies owner and group of the parent directory of `path` to `path` itself, e.g. for files extracted from an archive.
+///
+/// The parent is taken lexically with [`Path::parent`]; a relative path of a single component uses the current directory.
+/// Symbolic links are not followed on `path` so a link pointing elsewhere does not change its target.
+/// A path without a parent, such as `/`, is reported as [`FileOwnerError::Io`] with [`io::ErrorKind::InvalidInput`].
+pub fn inherit_parent_ownership(path: impl AsRef<Path>) -> Result<(), FileOwnerError> {
+    let path = path.as_ref();
+    let parent = match path.parent() {
+        Some(parent) if parent.as_os_str().is_empty() => Path::new("."),
+        Some(parent) => parent,
+        None => return Err(FileOwnerError::Io { path: path.to_owned(), source: io::Error::new(io::ErrorKind::InvalidInput, "path has no parent directory") }),
+    };
+    let (owner, group) = owner_group(parent)?;
+    chown_ids(path, Some(owner.0), Some(group.0), false)
+}
+
+/// Checks whether files at paths `a` and `b` have the same owner and group.
+pub fn same_ownership(a: impl AsRef<Path>, b: impl AsRef<Path>) -> Result<bool, FileOwnerError> {
+    Ok(diff_ownership(a, b)?.is_same())
+}
+
+/// Compares owner and group of files at paths `a` and `b`.
+pub fn diff_ownership(a: impl AsRef<Path>, b: impl AsRef<Path>) -> Result<OwnershipDiff, FileOwnerError> {
+    Ok(ownership(a)?.diff(&ownership(b)?))
+}
+
+/// Exchanges owner and group of files at paths `a` and `b` and returns their previous ownership.
+///
+/// This is not atomic: it takes two `chown` calls so other processes may observe both files with the same ownership in between.
+/// If changing `b` fails the previous ownership of `a` is restored on a best-effort basis and the error is returned.
+pub fn swap_ownership(a: impl AsRef<Path>, b: impl AsRef<Path>) -> Result<(Ownership, Ownership), FileOwnerError> {
+    let (a, b) = (a.as_ref(), b.as_ref());
+    let (ownership_a, ownership_b) = (ownership(a)?, ownership(b)?);
+    set_ownership(a, &ownership_b)?;
+    if let Err(err) = set_ownership(b, &ownership_a) {
+        let _ = set_ownership(a, &ownership_a);
+        return Err(err);
+    }
+    Ok((ownership_a, ownership_b))
+}
+
+/// Sets owner and then group of a file at the given path, restoring the original owner if setting the group fails.
+///
+/// Prefer [`set_owner_group`] which changes both with a single atomic `chown`; this is for callers that need two separate steps.
+/// If the group step fails and the owner is restored, [`FileOwnerError::RolledBack`] is returned with the original ownership and the group error.
+/// If restoring the owner fails as well, the group error is returned as is.
+pub fn set_owner_then_group_rollback<E1: Into<FileOwnerError>, E2: Into<FileOwnerError>>(path: impl AsRef<Path>, owner: impl TryInto<Owner, Error = E1>, group: impl TryInto<Group, Error = E2>) -> Result<(), FileOwnerError> {
+    let owner = owner.try_into().map_err(Into::into)?;
+    let group = group.try_into().map_err(Into::into)?;
+    owner_then_group_rollback(path.as_ref(), owner, |path| chown_ids(path, None, Some(group.0), true))
+}
+
+fn owner_then_group_rollback(path: &Path, owner: Owner, set_group: impl FnOnce(&Path) -> Result<(), FileOwnerError>) -> Result<(), FileOwnerError> {
+    let original = ownership(path)?;
+    chown_ids(path, Some(owner.0), None, true)?;
+    if let Err(err) = set_group(path) {
+        return match chown_ids(path, Some(original.owner.0), None, true) {
+            Ok(()) => Err(FileOwnerError::RolledBack { original, source: Box::new(err) }),
+            Err(_) => Err(err),
+        };
+    }
+    Ok(())
+}
+
+/// Copies contents of file at `src` path to `dst` path with `fs::copy` and then applies the owner and group of `src` to `dst`.
+///
+/// Returns the number of bytes copied. Setting an owner other than the calling user requires privileges (`CAP_CHOWN`),
+/// so without them this fails with [`FileOwnerError::PermissionDenied`] after the contents were already copied.
+/// Failure to copy is reported as [`FileOwnerError::Io`] with the `dst` path.
+pub fn copy_with_ownership(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> Result<u64, FileOwnerError> {
+    let (src, dst) = (src.as_ref(), dst.as_ref());
+    let (owner, group) = owner_group(src)?;
+    let copied = fs::copy(src, dst).map_err(|source| FileOwnerError::Io { path: dst.to_owned(), source })?;
+    chown_ids(dst, Some(owner.0), Some(group.0), true)?;
+    Ok(copied)
+}
+
+/// Sets owner and/or group to file at the given path as specified.
+///
+/// Only the parts present in the spec are changed, with a single `chown` call.
+pub fn set_spec(path: impl AsRef<Path>, spec: &OwnerGroupSpec) -> Result<(), FileOwnerError> {
+    chown_ids(path.as_ref(), spec.owner.map(|owner| owner.0), spec.group.map(|group| group.0), true)
+}
+
+/// Applies owner/group specifications to their paths, continuing past errors.
+///
+/// Entries are applied in order with [`set_spec`] and all the per-path failures are returned.
+/// Specifications hold already resolved ids; parse them with a shared [`Resolver`] so names repeated across the manifest are looked up once.
+pub fn apply_manifest(entries: impl IntoIterator<Item = (PathBuf, OwnerGroupSpec)>) -> Result<(), Vec<(PathBuf, FileOwnerError)>> {
+    let errors = entries.into_iter()
+        .filter_map(|(path, spec)| set_spec(&path, &spec).err().map(|err| (path, err)))
+        .collect::<Vec<_>>();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Sets owner to file at the given path checking first that the UID is assigned to a user.
+///
+/// Unlike [`set_owner`], which like `chown` accepts any UID, this returns [`FileOwnerError::UidNotFound`]
+/// instead of creating files owned by a nonexistent user.
+pub fn set_owner_checked<E: Into<FileOwnerError>>(path: impl AsRef<Path>, owner: impl TryInto<Owner, Error = E>) -> Result<(), FileOwnerError> {
+    let owner = owner.try_into().map_err(Into::into)?;
+    if !owner.exists()? {
+        return Err(FileOwnerError::UidNotFound(owner.id()));
+    }
+    chown_ids(path.as_ref(), Some(owner.0), None, true)
+}
+
+/// Sets owner to file at the given path after resolving it with `fs::canonicalize`.
+///
+/// All symlinks and `..` components of the path are resolved first and the owner is set on the resulting real path.
+/// Failure to canonicalize is reported as [`FileOwnerError::PathNotFound`] or [`FileOwnerError::Io`] with the original path.
+pub fn set_owner_canonical<E: Into<FileOwnerError>>(path: impl AsRef<Path>, owner: impl TryInto<Owner, Error = E>) -> Result<(), FileOwnerError> {
+    let owner = owner.try_into().map_err(Into::into)?;
+    let path = path.as_ref();
+    let real_path = fs::canonicalize(path).map_err(|source| path_io_error(path, source))?;
+    chown_ids(&real_path, Some(owner.0), None, false)
+}
+
+/// Sets owner to file at the given path only if it differs from the current one.
+///
+/// Returns `true` if the owner was changed. When unchanged no `chown` call is made, so ctime is preserved.
+pub fn set_owner_if_changed<E: Into<FileOwnerError>>(path: impl AsRef<Path>, owner: impl TryInto<Owner, Error = E>) -> Result<bool, FileOwnerError> {
+    chown_if_changed(path.as_ref(), Some(owner.try_into().map_err(Into::into)?), None)
+}
+
+/// Sets group to file at the given path only if it differs from the current one.
+///
+/// Returns `true` if the group was changed. When unchanged no `chown` call is made, so ctime is preserved.
+pub fn set_group_if_changed<E: Into<FileOwnerError>>(path: impl AsRef<Path>, group: impl TryInto<Group, Error = E>) -> Result<bool, FileOwnerError> {
+    chown_if_changed(path.as_ref(), None, Some(group.try_into().map_err(Into::into)?))
+}
+
+/// Sets owner and group to file at the given path only if either differs from the current ones.
+///
+/// Returns `true` if the ownership was changed. When unchanged no `chown` call is made, so ctime is preserved.
+pub fn set_owner_group_if_changed<E1: Into<FileOwnerError>, E2: Into<FileOwnerError>>(path: impl AsRef<Path>, owner: impl TryInto<Owner, Error = E1>, group: impl TryInto<Group, Error = E2>) -> Result<bool, FileOwnerError> {
+    chown_if_changed(path.as_ref(), Some(owner.try_into().map_err(Into::into)?), Some(group.try_into().map_err(Into::into)?))
+}
+
+/// Sets owner to file at the given path and returns the previous owner.
+pub fn replace_owner<E: Into<FileOwnerError>>(path: impl AsRef<Path>, owner: impl TryInto<Owner, Error = E>) -> Result<Owner, FileOwnerError> {
+    let owner = owner.try_into().map_err(Into::into)?;
+    let previous = self::owner(path.as_ref())?;
+    chown_ids(path.as_ref(), Some(owner.0), None, true)?;
+    Ok(previous)
+}
+
+/// Sets group to file at the given path and returns the previous group.
+pub fn replace_group<E: Into<FileOwnerError>>(path: impl AsRef<Path>, group: impl TryInto<Group, Error = E>) -> Result<Group, FileOwnerError> {
+    let group = group.try_into().map_err(Into::into)?;
+    let previous = self::group(path.as_ref())?;
+    chown_ids(path.as_ref(), None, Some(group.0), true)?;
+    Ok(previous)
+}
+
+/// Sets owner and group to file at the given path and returns the previous owner and group.
+pub fn replace_owner_group<E1: Into<FileOwnerError>, E2: Into<FileOwnerError>>(path: impl AsRef<Path>, owner: impl TryInto<Owner, Error = E1>, group: impl TryInto<Group, Error = E2>) -> Result<(Owner, Group), FileOwnerError> {
+    let owner = owner.try_into().map_err(Into::into)?;
+    let group = group.try_into().map_err(Into::into)?;
+    let previous = owner_group(path.as_ref())?;
+    chown_ids(path.as_ref(), Some(owner.0), Some(group.0), true)?;
+    Ok(previous)
+}
+
+/// Sets owner to file at the given path and reports the change to `log`.
+///
+/// After a successful `chown` the callback receives the path, the previous and the new owner. It is not called on failure.
+pub fn set_owner_logged<E: Into<FileOwnerError>>(path: impl AsRef<Path>, owner: impl TryInto<Owner, Error = E>, mut log: impl FnMut(&Path, Owner, Owner)) -> Result<(), FileOwnerError> {
+    let owner = owner.try_into().map_err(Into::into)?;
+    let path = path.as_ref();
+    log(path, replace_owner(path, owner)?, owner);
+    Ok(())
+}
+
+/// Sets group to file at the given path and reports the change to `log`.
+///
+/// After a successful `chown` the callback receives the path, the previous and the new group. It is not called on failure.
+pub fn set_group_logged<E: Into<FileOwnerError>>(path: impl AsRef<Path>, group: impl TryInto<Group, Error = E>, mut log: impl FnMut(&Path, Group, Group)) -> Result<(), FileOwnerError> {
+    let group = group.try_into().map_err(Into::into)?;
+    let path = path.as_ref();
+    log(path, replace_group(path, group)?, group);
+    Ok(())
+}
+
+/// Sets owner and group to file at the given path and reports the change to `log`.
+///
+/// After a successful `chown` the callback receives the path, the previous and the new ownership. It is not called on failure.
+pub fn set_owner_group_logged<E1: Into<FileOwnerError>, E2: Into<FileOwnerError>>(path: impl AsRef<Path>, owner: impl TryInto<Owner, Error = E1>, group: impl TryInto<Group, Error = E2>, mut log: impl FnMut(&Path, Ownership, Ownership)) -> Result<(), FileOwnerError> {
+    let ownership = Ownership::new(owner.try_into().map_err(Into::into)?, group.try_into().map_err(Into::into)?);
+    let path = path.as_ref();
+    log(path, replace_owner_group(path, ownership.owner, ownership.group)?.into(), ownership);
+    Ok(())
+}
+
+/// Ownership change of a file computed without applying it.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct OwnershipChange {
+    pub path: PathBuf,
+    pub current: Ownership,
+    pub proposed: Ownership,
+}
+
+impl OwnershipChange {
+    /// Checks whether applying the change would modify the ownership.
+    pub fn is_change(&self) -> bool {
+        self.current != self.proposed
+    }
+
+    /// Applies the proposed ownership to the file with a single `chown` call.
+    pub fn apply(&self) -> Result<(), FileOwnerError> {
+        chown_ids(&self.path, Some(self.proposed.owner.0), Some(self.proposed.group.0), true)
+    }
+}
+
+/// Computes the change [`set_owner`] would make to file at the given path without applying it.
+///
+/// The group is kept as is. Use [`OwnershipChange::apply`] to perform the change later.
+pub fn plan_set_owner<E: Into<FileOwnerError>>(path: impl AsRef<Path>, owner: impl TryInto<Owner, Error = E>) -> Result<OwnershipChange, FileOwnerError> {
+    let owner = owner.try_into().map_err(Into::into)?;
+    let path = path.as_ref();
+    let current = ownership(path)?;
+    Ok(OwnershipChange { path: path.to_owned(), current, proposed: Ownership::new(owner, current.group) })
+}
+
+fn chown_if_changed(path: &Path, owner: Option<Owner>, group: Option<Group>) -> Result<bool, FileOwnerError> {
+    let (current_owner, current_group) = owner_group(path)?;
+    let owner = owner.filter(|owner| *owner != current_owner);
+    let group = group.filter(|group| *group != current_group);
+    if owner.is_none() && group.is_none() {
+        return Ok(false);
+    }
+    chown_ids(path, owner.map(|owner| owner.0), group.map(|group| group.0), true)?;
+    Ok(true)
+}
+
+/// Extension methods for `T: AsRef<Path>`.
+pub trait PathExt {
+    /// Sets owner to file at the given path.
+    fn set_owner<E: Into<FileOwnerError>>(&self, owner: impl TryInto<Owner, Error = E>) -> Result<(), FileOwnerError>;
+
+    /// Sets owner to file at the given path without following symlinks.
+    fn set_owner_no_follow<E: Into<FileOwnerError>>(&self, owner: impl TryInto<Owner, Error = E>) -> Result<(), FileOwnerError>;
+
+    /// Sets group to file at the given path.
+    fn set_group<E: Into<FileOwnerError>>(&self, group: impl TryInto<Group, Error = E>) -> Result<(), FileOwnerError>;
+
+    /// Sets group to file at the given path without following symlinks.
+    fn set_group_no_follow<E: Into<FileOwnerError>>(&self, group: impl TryInto<Group, Error = E>) -> Result<(), FileOwnerError>;
+
+    /// Sets owner and group to file at the given path.
+    fn set_owner_group<E1: Into<FileOwnerError>, E2: Into<FileOwnerError>>(&self, owner: impl TryInto<Owner, Error = E1>, group: impl TryInto<Group, Error = E2>) -> Result<(), FileOwnerError>;
+
+    /// Sets owner and group to file at the given path without following symlinks.
+    fn set_owner_group_no_follow<E1: Into<FileOwnerError>, E2: Into<FileOwnerError>>(&self, owner: impl TryInto<Owner, Error = E1>, group: impl TryInto<Group, Error = E2>) -> Result<(), FileOwnerError>;
+
+    /// Gets owner of a file at the given path.
+    fn owner(&self) -> Result<Owner, FileOwnerError>;
+
+    /// Gets owner of a file at the given path without following symlinks.
+    fn owner_no_follow(&self) -> Result<Owner, FileOwnerError>;
+
+    /// Gets group of a file at the given path.
+    fn group(&self) -> Result<Group, FileOwnerError>;
+
+    /// Gets group of a file at the given path without following symlinks.
+    fn group_no_follow(&self) -> Result<Group, FileOwnerError>;
+
+    /// Gets owner and group of a file at the given path.
+    fn owner_group(&self) -> Result<(Owner, Group), FileOwnerError>;
+
+    /// Gets owner and group of a file at the given path without following symlinks.
+    fn owner_group_no_follow(&self) -> Result<(Owner, Group), FileOwnerError>;
+
+    /// Gets ownership of a file at the given path.
+    fn ownership(&self) -> Result<Ownership, FileOwnerError>;
+
+    /// Gets owner, group and permission bits of a file at the given path with a single `stat` call.
+    fn ownership_mode(&self) -> Result<(Owner, Group, u32), FileOwnerError>;
+
+    /// Sets ownership to file at the given path.
+    fn set_ownership(&self, ownership: &Ownership) -> Result<(), FileOwnerError>;
+
+    /// Sets owner and/or group to file at the given path as specified.
+    fn set_spec(&self, spec: &OwnerGroupSpec) -> Result<(), FileOwnerError>;
+
+    /// Checks whether file at the given path is owned by the given user.
+    fn is_owned_by<E: Into<FileOwnerError>>(&self, owner: impl TryInto<Owner, Error = E>) -> Result<bool, FileOwnerError>;
+
+    /// Checks whether file at the given path belongs to the given group.
+    fn is_group<E: Into<FileOwnerError>>(&self, group: impl TryInto<Group, Error = E>) -> Result<bool, FileOwnerError>;
+}
+
+impl<T: AsRef<Path>> PathExt for T {
+    fn set_owner<E: Into<FileOwnerError>>(&self, owner: impl TryInto<Owner, Error = E>) -> Result<(), FileOwnerError> {
+        set_owner(self, owner)
+    }
+
+    fn set_owner_no_follow<E: Into<FileOwnerError>>(&self, owner: impl TryInto<Owner, Error = E>) -> Result<(), FileOwnerError> {
+        set_owner_no_follow(self, owner)
+    }
+
+    fn set_group<E: Into<FileOwnerError>>(&self, group: impl TryInto<Group, Error = E>) -> Result<(), FileOwnerError> {
+        set_group(self, group)
+    }
+
+    fn set_group_no_follow<E: Into<FileOwnerError>>(&self, group: impl TryInto<Group, Error = E>) -> Result<(), FileOwnerError> {
+        set_group_no_follow(self, group)
+    }
+
+    fn set_owner_group<E1: Into<FileOwnerError>, E2: Into<FileOwnerError>>(&self, owner: impl TryInto<Owner, Error = E1>, group: impl TryInto<Group, Error = E2>) -> Result<(), FileOwnerError> {
+        set_owner_group(self, owner, group)
+    }
+
+    fn set_owner_group_no_follow<E1: Into<FileOwnerError>, E2: Into<FileOwnerError>>(&self, owner: impl TryInto<Owner, Error = E1>, group: impl TryInto<Group, Error = E2>) -> Result<(), FileOwnerError> {
+        set_owner_group_no_follow(self, owner, group)
+    }
+
+    fn owner(&self) -> Result<Owner, FileOwnerError> {
+        owner(self)
+    }
+
+    fn owner_no_follow(&self) -> Result<Owner, FileOwnerError> {
+        owner_no_follow(self)
+    }
+
+    fn group(&self) -> Result<Group, FileOwnerError> {
+        group(self)
+    }
+
+    fn group_no_follow(&self) -> Result<Group, FileOwnerError> {
+        group_no_follow(self)
+    }
+
+    fn owner_group(&self) -> Result<(Owner, Group), FileOwnerError> {
+        owner_group(self)
+    }
+
+    fn owner_group_no_follow(&self) -> Result<(Owner, Group), FileOwnerError> {
+        owner_group_no_follow(self)
+    }
+
+    fn ownership(&self) -> Result<Ownership, FileOwnerError> {
+        ownership(self)
+    }
+
+    fn ownership_mode(&self) -> Result<(Owner, Group, u32), FileOwnerError> {
+        ownership_mode(self)
+    }
+
+    fn set_ownership(&self, ownership: &Ownership) -> Result<(), FileOwnerError> {
+        set_ownership(self, ownership)
+    }
+
+    fn set_spec(&self, spec: &OwnerGroupSpec) -> Result<(), FileOwnerError> {
+        set_spec(self, spec)
+    }
+
+    fn is_owned_by<E: Into<FileOwnerError>>(&self, owner: impl TryInto<Owner, Error = E>) -> Result<bool, FileOwnerError> {
+        is_owned_by(self, owner)
+    }
+
+    fn is_group<E: Into<FileOwnerError>>(&self, group: impl TryInto<Group, Error = E>) -> Result<bool, FileOwnerError> {
+        is_group(self, group)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Resolver, UserCache};
+    use std::error::Error;
+    use super::*;
+
+    #[test]
+    #[cfg(not(feature = "display-numeric"))]
+    fn test_display() {
+        let nobody_id = Owner::from_name("nobody").unwrap().id();
+        let nogroup_id = Group::from_name("nogroup").unwrap().id();
+
+        assert_eq!(&Owner::from_uid(nobody_id).to_string(), "nobody");
+        assert_eq!(&Group::from_gid(nogroup_id).to_string(), "nogroup");
+
+        assert_eq!(&Owner::from_uid(321321).to_string(), "321321");
+        assert_eq!(&Group::from_gid(321321).to_string(), "321321");
+    }
+
+    #[test]
+    #[ignore]
+    fn test_set_get() {
+        let nobody_id = Owner::from_name("nobody").unwrap().id();
+        let nogroup_id = Group::from_name("nogroup").unwrap().id();
+
+        let file1 = tempfile::NamedTempFile::new().unwrap();
+        let file_path1 = file1.path();
+        let file2 = tempfile::NamedTempFile::new().unwrap();
+        let file_path2 = file2.path();
+
+        set_owner(file_path1, "nobody").unwrap();
+        assert_eq!(owner(file_path1).unwrap().name().unwrap().as_deref(), Some("nobody"));
+        set_owner(file_path2, nobody_id).unwrap();
+        assert_eq!(owner(file_path2).unwrap().name().unwrap().as_deref(), Some("nobody"));
+
+        set_group(file_path1, "nogroup").unwrap();
+        assert_eq!(group(file_path1).unwrap().name().unwrap().as_deref(), Some("nogroup"));
+        set_group(file_path2, nogroup_id).unwrap();
+        assert_eq!(group(file_path2).unwrap().name().unwrap().as_deref(), Some("nogroup"));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_set_get_all() {
+        let nobody_id = Owner::from_name("nobody").unwrap().id();
+        let nogroup_id = Group::from_name("nogroup").unwrap().id();
+
+        let file1 = tempfile::NamedTempFile::new().unwrap();
+        let file_path1 = file1.path();
+        let file2 = tempfile::NamedTempFile::new().unwrap();
+        let file_path2 = file2.path();
+        let file3 = tempfile::NamedTempFile::new().unwrap();
+        let file_path3 = file3.path();
+        let file4 = tempfile::NamedTempFile::new().unwrap();
+        let file_path4 = file4.path();
+
+        set_owner_group(file_path1, "nobody", "nogroup").unwrap();
+        assert_eq!(owner(file_path1).unwrap().name().unwrap().as_deref(), Some("nobody"));
+        assert_eq!(group(file_path1).unwrap().name().unwrap().as_deref(), Some("nogroup"));
+        set_owner_group(file_path2, nobody_id, nogroup_id).unwrap();
+        assert_eq!(owner(file_path2).unwrap().name().unwrap().as_deref(), Some("nobody"));
+        assert_eq!(group(file_path2).unwrap().name().unwrap().as_deref(), Some("nogroup"));
+
+        set_owner_group(file_path3, nobody_id, "nogroup").unwrap();
+        assert_eq!(owner(file_path3).unwrap().name().unwrap().as_deref(), Some("nobody"));
+        assert_eq!(group(file_path3).unwrap().name().unwrap().as_deref(), Some("nogroup"));
+        set_owner_group(file_path4, "nobody", nogroup_id).unwrap();
+        assert_eq!(owner(file_path4).unwrap().name().unwrap().as_deref(), Some("nobody"));
+        assert_eq!(group(file_path4).unwrap().name().unwrap().as_deref(), Some("nogroup"));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_get_all() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let file_path = file.path();
+
+        set_owner_group(file_path, "nobody", "nogroup").unwrap();
+
+        let (o, g) = owner_group(file_path).unwrap();
+        assert_eq!(o.name().unwrap().as_deref(), Some("nobody"));
+        assert_eq!(g.name().unwrap().as_deref(), Some("nogroup"));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_ext_traits() {
+        let nobody_id = Owner::from_name("nobody").unwrap().id();
+        let nogroup_id = Group::from_name("nogroup").unwrap().id();
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let file_path = file.path();
+
+        file_path.set_owner("nobody").unwrap();
+        file_path.set_group("nogroup").unwrap();
+
+        assert_eq!(file_path.owner().unwrap().name().unwrap().as_deref(), Some("nobody"));
+        assert_eq!(file_path.group().unwrap().name().unwrap().as_deref(), Some("nogroup"));
+
+        file_path.set_owner_group("nobody", "nogroup").unwrap();
+
+        let (o, g) = file_path.owner_group().unwrap();
+        assert_eq!(o.name().unwrap().as_deref(), Some("nobody"));
+        assert_eq!(g.name().unwrap().as_deref(), Some("nogroup"));
+
+        assert_eq!(o.id(), nobody_id);
+        assert_eq!(g.id(), nogroup_id);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_set_owner_no_follow() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("target");
+        let link = dir.path().join("link");
+        fs::write(&target, b"").unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        let target_owner = owner(&target).unwrap();
+
+        set_owner_no_follow(&link, "nobody").unwrap();
+        assert_eq!(owner(&target).unwrap(), target_owner);
+        assert_eq!(Owner::from_uid(fs::symlink_metadata(&link).unwrap().uid()).name().unwrap().as_deref(), Some("nobody"));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_set_group_no_follow_dangling() {
+        let dir = tempfile::tempdir().unwrap();
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink(dir.path().join("missing"), &link).unwrap();
+
+        assert!(matches!(set_group(&link, "nogroup"), Err(FileOwnerError::PathNotFound(_))));
+
+        link.set_group_no_follow("nogroup").unwrap();
+        assert_eq!(Group::from_gid(fs::symlink_metadata(&link).unwrap().gid()).name().unwrap().as_deref(), Some("nogroup"));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_set_owner_group_no_follow_dangling() {
+        let dir = tempfile::tempdir().unwrap();
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink(dir.path().join("missing"), &link).unwrap();
+
+        link.set_owner_group_no_follow("nobody", "nogroup").unwrap();
+
+        let meta = fs::symlink_metadata(&link).unwrap();
+        assert_eq!(Owner::from_uid(meta.uid()).name().unwrap().as_deref(), Some("nobody"));
+        assert_eq!(Group::from_gid(meta.gid()).name().unwrap().as_deref(), Some("nogroup"));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_owner_no_follow() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("target");
+        let link = dir.path().join("link");
+        let dangling = dir.path().join("dangling");
+        fs::write(&target, b"").unwrap();
+        set_owner(&target, 0).unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+        std::os::unix::fs::symlink(dir.path().join("missing"), &dangling).unwrap();
+
+        link.set_owner_no_follow("nobody").unwrap();
+        dangling.set_owner_no_follow("nobody").unwrap();
+
+        assert_eq!(link.owner().unwrap().id(), 0);
+        assert_eq!(link.owner_no_follow().unwrap().name().unwrap().as_deref(), Some("nobody"));
+        assert_eq!(dangling.owner_no_follow().unwrap().name().unwrap().as_deref(), Some("nobody"));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_group_no_follow() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("target");
+        let link = dir.path().join("link");
+        fs::write(&target, b"").unwrap();
+        set_owner_group(&target, 0, 0).unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        link.set_owner_group_no_follow("nobody", "nogroup").unwrap();
+
+        assert_eq!(link.group().unwrap().id(), 0);
+        assert_eq!(link.group_no_follow().unwrap().name().unwrap().as_deref(), Some("nogroup"));
+
+        let (o, g) = link.owner_group_no_follow().unwrap();
+        assert_eq!(o.name().unwrap().as_deref(), Some("nobody"));
+        assert_eq!(g.name().unwrap().as_deref(), Some("nogroup"));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_set_with_follow() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("target");
+        let link = dir.path().join("link");
+        fs::write(&target, b"").unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+        set_owner_group_no_follow(&link, 0, 0).unwrap();
+        set_owner_group(&target, 0, 0).unwrap();
+
+        set_owner_with(&link, "nobody", false).unwrap();
+        set_group_with(&link, "nogroup", false).unwrap();
+        assert_eq!(owner_group(&target).unwrap(), (Owner::from_uid(0), Group::from_gid(0)));
+        assert_eq!(owner_group_no_follow(&link).unwrap(), (Owner::from_name("nobody").unwrap(), Group::from_name("nogroup").unwrap()));
+
+        set_owner_group_with(&link, "nobody", "nogroup", true).unwrap();
+        assert_eq!(owner_group(&target).unwrap(), (Owner::from_name("nobody").unwrap(), Group::from_name("nogroup").unwrap()));
+
+        set_owner_group_with(&link, 0, 0, false).unwrap();
+        assert_eq!(owner_group_no_follow(&link).unwrap(), (Owner::from_uid(0), Group::from_gid(0)));
+        assert_eq!(owner_group(&target).unwrap(), (Owner::from_name("nobody").unwrap(), Group::from_name("nogroup").unwrap()));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_set_owner_fd() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+
+        set_owner_fd(file.as_file().as_raw_fd(), "nobody").unwrap();
+        assert_eq!(owner(file.path()).unwrap().name().unwrap().as_deref(), Some("nobody"));
+
+        set_owner_file(file.as_file(), 0).unwrap();
+        assert_eq!(owner(file.path()).unwrap().id(), 0);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_owner_fd() {
+        let file = tempfile::tempfile().unwrap();
+
+        set_owner_file(&file, "nobody").unwrap();
+        assert_eq!(owner_fd(file.as_raw_fd()).unwrap().name().unwrap().as_deref(), Some("nobody"));
+        assert_eq!(owner_file(&file).unwrap().name().unwrap().as_deref(), Some("nobody"));
+
+        let (o, g) = owner_group_file(&file).unwrap();
+        assert_eq!(o, owner_file(&file).unwrap());
+        assert_eq!(g, group_file(&file).unwrap());
+        assert_eq!(g, group_fd(file.as_raw_fd()).unwrap());
+    }
+
+    #[test]
+    #[ignore]
+    fn test_set_owner_at() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("child"), b"").unwrap();
+        std::os::unix::fs::symlink("child", dir.path().join("link")).unwrap();
+        let dir_file = File::open(dir.path()).unwrap();
+
+        set_owner_at(dir_file.as_raw_fd(), "link", "nobody", false).unwrap();
+        assert_eq!(owner(dir.path().join("child")).unwrap().id(), 0);
+        assert_eq!(owner_no_follow(dir.path().join("link")).unwrap().name().unwrap().as_deref(), Some("nobody"));
+
+        set_owner_at(dir_file.as_raw_fd(), "child", "nobody", true).unwrap();
+        assert_eq!(owner(dir.path().join("child")).unwrap().name().unwrap().as_deref(), Some("nobody"));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_set_owner_many() {
+        let files = (0..3).map(|_| tempfile::NamedTempFile::new().unwrap()).collect::<Vec<_>>();
+
+        assert_eq!(set_owner_many(files.iter().map(|f| f.path()), "nobody").unwrap(), 3);
+        for file in &files {
+            assert_eq!(owner(file.path()).unwrap().name().unwrap().as_deref(), Some("nobody"));
+        }
+
+        let missing = files[0].path().with_extension("missing");
+        let errors = set_owner_many(vec![files[0].path(), &missing, files[1].path()], 0).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, missing);
+        assert_eq!(owner(files[1].path()).unwrap().id(), 0);
+    }
+
+    #[test]
+    fn test_ownership_constructors() {
+        let nobody_id = Owner::from_name("nobody").unwrap().id();
+        let nogroup_id = Group::from_name("nogroup").unwrap().id();
+
+        assert_eq!(Ownership::from_names("nobody", "nogroup").unwrap(), Ownership::from_ids(nobody_id, nogroup_id));
+        assert!(matches!(Ownership::from_names("nobody", "no such group"), Err(FileOwnerError::GroupNotFound(_))));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_ownership_round_trip() {
+        let file1 = tempfile::NamedTempFile::new().unwrap();
+        let file2 = tempfile::NamedTempFile::new().unwrap();
+
+        let ownership = Ownership::from_names("nobody", "nogroup").unwrap();
+        file1.path().set_ownership(&ownership).unwrap();
+        assert_eq!(file1.path().ownership().unwrap(), ownership);
+
+        let snapshot = file2.path().ownership().unwrap();
+        file2.path().set_ownership(&ownership).unwrap();
+        file2.path().set_ownership(&snapshot).unwrap();
+        assert_eq!(file2.path().ownership().unwrap(), snapshot);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_copy_ownership() {
+        let src = tempfile::NamedTempFile::new().unwrap();
+        let dst = tempfile::NamedTempFile::new().unwrap();
+        set_owner_group(src.path(), "nobody", "nogroup").unwrap();
+
+        copy_ownership(src.path(), dst.path()).unwrap();
+        assert_eq!(owner_group(dst.path()).unwrap(), owner_group(src.path()).unwrap());
+
+        let dir = tempfile::tempdir().unwrap();
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink(dir.path().join("missing"), &link).unwrap();
+        copy_ownership_no_follow(src.path(), &link).unwrap();
+        assert_eq!(owner_group_no_follow(&link).unwrap(), owner_group(src.path()).unwrap());
+
+        match copy_ownership(dir.path().join("missing"), dst.path()) {
+            Err(FileOwnerError::PathNotFound(path)) => assert_eq!(path, dir.path().join("missing")),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_io_error_path() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let path = file.path().join("child");
+
+        let err = owner(&path).unwrap_err();
+        assert!(matches!(&err, FileOwnerError::Io { path: p, .. } if p == &path));
+        assert_eq!(err.to_string(), format!("I/O error on {:?}: {}", path, io::Error::from_raw_os_error(nix::libc::ENOTDIR)));
+        assert!(err.source().is_some());
+
+        assert!(matches!(group_no_follow(&path), Err(FileOwnerError::Io { .. })));
+        assert!(matches!(owner_group(&path), Err(FileOwnerError::Io { .. })));
+    }
+
+    #[test]
+    fn test_error_display() {
+        let io_err = io::Error::other("disk on fire");
+        assert_eq!(FileOwnerError::from(io_err).to_string(), "I/O error: disk on fire");
+
+        let nix_err = FileOwnerError::from(nix::Error::EPERM);
+        assert!(nix_err.to_string().starts_with("*nix error: "));
+        assert!(nix_err.to_string().contains(nix::Error::EPERM.desc()));
+
+        let entry_err = FileOwnerError::EntryError(PathBuf::from("/foo"), Box::new(FileOwnerError::UserNotFound("bar".to_owned())));
+        assert_eq!(entry_err.to_string(), "error processing \"/foo\": user name \"bar\" not found");
+
+        let rolled_back = FileOwnerError::RolledBack { original: Ownership::new(Owner::from_uid(1), Group::from_gid(2)), source: Box::new(FileOwnerError::PermissionDenied { path: PathBuf::from("/foo") }) };
+        assert_eq!(rolled_back.to_string(), "permission denied changing ownership of \"/foo\" (owner restored to 1)");
+        assert_eq!(rolled_back.errno(), Some(Errno::EPERM));
+    }
+
+    #[test]
+    fn test_permission_denied() {
+        assert!(matches!(chown_error(Path::new("/foo"), Errno::EPERM), FileOwnerError::PermissionDenied { path } if path == Path::new("/foo")));
+        assert!(matches!(chown_error(Path::new("/foo"), Errno::EACCES), FileOwnerError::PermissionDenied { .. }));
+        assert!(matches!(chown_error(Path::new("/foo"), Errno::EROFS), FileOwnerError::NixError(Errno::EROFS)));
+        assert_eq!(FileOwnerError::PermissionDenied { path: PathBuf::from("/foo") }.to_string(), "permission denied changing ownership of \"/foo\"");
+
+        // only a non-root user gets denied
+        if Uid::effective().is_root() {
+            return;
+        }
+        let file = tempfile::NamedTempFile::new().unwrap();
+        match set_owner(file.path(), 0) {
+            Err(FileOwnerError::PermissionDenied { path }) => assert_eq!(path, file.path()),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_path_not_found() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing");
+
+        assert!(matches!(owner(&missing), Err(FileOwnerError::PathNotFound(path)) if path == missing));
+        assert!(matches!(owner_group_no_follow(&missing), Err(FileOwnerError::PathNotFound(path)) if path == missing));
+        assert!(matches!(set_owner(&missing, Uid::effective().as_raw()), Err(FileOwnerError::PathNotFound(path)) if path == missing));
+        assert!(matches!(set_group_no_follow(&missing, Gid::effective().as_raw()), Err(FileOwnerError::PathNotFound(path)) if path == missing));
+        assert_eq!(FileOwnerError::PathNotFound(missing.clone()).to_string(), format!("path {:?} not found", missing));
+    }
+
+    #[test]
+    fn test_id_boundaries() {
+        for &id in &[0, 1, 65534, 65535, 65536, i32::MAX as u32, u32::MAX - 1, u32::MAX] {
+            assert_eq!(Owner::from_uid(id).id(), id);
+            assert_eq!(Group::from_gid(id).id(), id);
+        }
+        assert_eq!(Owner::from(u32::MAX), Owner::from_uid(u32::MAX));
+        assert_eq!(Owner::from_uid(u32::MAX).to_string(), "4294967295");
+        assert_eq!(Group::from_gid(u32::MAX).to_string(), "4294967295");
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)] // uid_t and gid_t are not u32 on all platforms
+    fn test_raw_boundaries() {
+        for &uid in &[0, 1, uid_t::MAX - 1, uid_t::MAX, uid_t::MIN] {
+            let owner = Owner::from_raw(uid);
+            assert_eq!(owner.as_raw(), uid);
+            assert_eq!(owner.as_uid(), Uid::from_raw(uid));
+            assert_eq!(owner.id(), uid as u32);
+            assert_eq!(format!("{:#}", owner), owner.id().to_string());
+        }
+        for &gid in &[0, 1, gid_t::MAX - 1, gid_t::MAX, gid_t::MIN] {
+            let group = Group::from_raw(gid);
+            assert_eq!(group.as_raw(), gid);
+            assert_eq!(group.as_gid(), Gid::from_raw(gid));
+            assert_eq!(group.id(), gid as u32);
+            assert_eq!(format!("{:#}", group), group.id().to_string());
+        }
+        assert_eq!(Owner::from_raw(uid_t::MAX).name().unwrap(), None);
+        assert_eq!(Owner::from_raw(uid_t::MAX).try_id().unwrap(), u32::MAX);
+        assert_eq!(Group::from_raw(gid_t::MAX), Group::from_gid(u32::MAX));
+    }
+
+    #[test]
+    fn test_try_id() {
+        for &id in &[0, 65534, u32::MAX] {
+            assert_eq!(Owner::from_uid(id).try_id().unwrap(), id);
+            assert_eq!(Group::from_gid(id).try_id().unwrap(), id);
+        }
+        assert_eq!(FileOwnerError::IdOutOfRange(-1).to_string(), "id -1 out of u32 range");
+    }
+
+    #[test]
+    fn test_owner_from_str() {
+        assert_eq!("nobody".parse::<Owner>().unwrap(), Owner::from_name("nobody").unwrap());
+        assert_eq!("99".parse::<Owner>().unwrap(), Owner::from_uid(99));
+        assert_eq!("4294967295".parse::<Owner>().unwrap(), Owner::from_uid(u32::MAX));
+        assert!(matches!("no such user".parse::<Owner>(), Err(FileOwnerError::UserNotFound(name)) if name == "no such user"));
+    }
+
+    #[test]
+    fn test_group_from_str() {
+        assert_eq!("nogroup".parse::<Group>().unwrap(), Group::from_name("nogroup").unwrap());
+        assert_eq!("29".parse::<Group>().unwrap(), Group::from_gid(29));
+        assert!(matches!("no such group".parse::<Group>(), Err(FileOwnerError::GroupNotFound(name)) if name == "no such group"));
+    }
+
+    #[test]
+    fn test_owner_group_spec_parse() {
+        let nobody = Owner::from_name("nobody").unwrap();
+        let nogroup = Group::from_name("nogroup").unwrap();
+
+        assert_eq!("nobody:nogroup".parse::<OwnerGroupSpec>().unwrap(), OwnerGroupSpec { owner: Some(nobody), group: Some(nogroup) });
+        assert_eq!("1000:1001".parse::<OwnerGroupSpec>().unwrap(), OwnerGroupSpec { owner: Some(Owner::from_uid(1000)), group: Some(Group::from_gid(1001)) });
+        assert_eq!("nobody".parse::<OwnerGroupSpec>().unwrap(), OwnerGroupSpec { owner: Some(nobody), group: None });
+        assert_eq!(":nogroup".parse::<OwnerGroupSpec>().unwrap(), OwnerGroupSpec { owner: None, group: Some(nogroup) });
+        assert_eq!("root:".parse::<OwnerGroupSpec>().unwrap(), OwnerGroupSpec { owner: Some(Owner::from_uid(0)), group: Some(Group::from_gid(0)) });
+        assert_eq!("".parse::<OwnerGroupSpec>().unwrap(), OwnerGroupSpec::default());
+        assert_eq!(":".parse::<OwnerGroupSpec>().unwrap(), OwnerGroupSpec::default());
+
+        assert!(matches!("no such user:nogroup".parse::<OwnerGroupSpec>(), Err(FileOwnerError::UserNotFound(_))));
+        assert!(matches!("nobody:no such group".parse::<OwnerGroupSpec>(), Err(FileOwnerError::GroupNotFound(_))));
+        assert!(matches!("321321:".parse::<OwnerGroupSpec>(), Err(FileOwnerError::UidNotFound(321321))));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_set_spec() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        set_owner_group(file.path(), 0, 0).unwrap();
+
+        file.path().set_spec(&":nogroup".parse().unwrap()).unwrap();
+        assert_eq!(owner(file.path()).unwrap().id(), 0);
+        assert_eq!(group(file.path()).unwrap().name().unwrap().as_deref(), Some("nogroup"));
+
+        file.path().set_spec(&"nobody".parse().unwrap()).unwrap();
+        assert_eq!(owner(file.path()).unwrap().name().unwrap().as_deref(), Some("nobody"));
+        assert_eq!(group(file.path()).unwrap().name().unwrap().as_deref(), Some("nogroup"));
+
+        file.path().set_spec(&"root:root".parse().unwrap()).unwrap();
+        assert_eq!(owner_group(file.path()).unwrap(), (Owner::from_uid(0), Group::from_gid(0)));
+    }
+
+    #[test]
+    fn test_current_owner_group() {
+        assert_eq!(current_owner().id(), geteuid().as_raw());
+        assert_eq!(current_group().id(), getegid().as_raw());
+        assert_eq!(real_owner().id(), getuid().as_raw());
+        assert_eq!(real_group().id(), getgid().as_raw());
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        assert_eq!(owner(file.path()).unwrap(), current_owner());
+    }
+
+    #[test]
+    fn test_home_dir() {
+        assert_eq!(Owner::from_name("root").unwrap().home_dir().unwrap().as_deref(), Some(Path::new("/root")));
+        assert_eq!(Owner::from_uid(321321).home_dir().unwrap(), None);
+
+        if let (Some(home), Some(env_home)) = (current_owner().home_dir().unwrap(), std::env::var_os("HOME")) {
+            assert_eq!(home, PathBuf::from(env_home));
+        }
+    }
+
+    #[test]
+    fn test_shell_gecos() {
+        let root = Owner::from_uid(0);
+        let user = root.user().unwrap().unwrap();
+
+        assert_eq!(root.shell().unwrap(), Some(user.shell.to_string_lossy().into_owned()));
+        assert_eq!(root.gecos().unwrap(), Some(user.gecos.to_string_lossy().into_owned()));
+        assert_eq!(root.name().unwrap(), Some(user.name));
+
+        assert_eq!(Owner::from_uid(321321).shell().unwrap(), None);
+        assert_eq!(Owner::from_uid(321321).gecos().unwrap(), None);
+    }
+
+    #[test]
+    fn test_primary_group() {
+        let user = User::from_uid(geteuid()).unwrap().unwrap();
+        assert_eq!(current_owner().primary_group().unwrap(), Group(user.gid));
+        assert!(matches!(Owner::from_uid(321321).primary_group(), Err(FileOwnerError::UidNotFound(321321))));
+        assert_eq!(FileOwnerError::UidNotFound(321321).to_string(), "no user with UID 321321");
+    }
+
+    #[test]
+    fn test_group_members() {
+        for name in &["root", "nogroup"] {
+            let group = Group::from_name(name).unwrap();
+            assert_eq!(group.members().unwrap(), NixGroup::from_name(name).unwrap().unwrap().mem);
+        }
+        assert!(matches!(Group::from_gid(321321).members(), Err(FileOwnerError::GidNotFound(321321))));
+        assert_eq!(FileOwnerError::GidNotFound(321321).to_string(), "no group with GID 321321");
+    }
+
+    #[test]
+    fn test_owner_groups() {
+        for user in &["root", "nobody"] {
+            let owner = Owner::from_name(user).unwrap();
+            let mut groups = owner.groups().unwrap();
+            assert_eq!(groups[0], owner.primary_group().unwrap());
+
+            let mut expected = nix::unistd::getgrouplist(&std::ffi::CString::new(*user).unwrap(), owner.primary_group().unwrap().0).unwrap().into_iter().map(Group).collect::<Vec<_>>();
+            groups.sort_by_key(Group::id);
+            expected.sort_by_key(Group::id);
+            expected.dedup();
+            assert_eq!(groups, expected);
+        }
+        assert!(matches!(Owner::from_uid(321321).groups(), Err(FileOwnerError::UidNotFound(321321))));
+    }
+
+    #[test]
+    fn test_if_changed_unchanged() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let (owner, group) = owner_group(file.path()).unwrap();
+        let ctime = |path: &Path| { let meta = fs::metadata(path).unwrap(); (meta.ctime(), meta.ctime_nsec()) };
+        let before = ctime(file.path());
+        std::thread::sleep(std::time::Duration::from_millis(20));
+
+        assert!(!set_owner_if_changed(file.path(), owner).unwrap());
+        assert!(!set_group_if_changed(file.path(), group).unwrap());
+        assert!(!set_owner_group_if_changed(file.path(), owner, group).unwrap());
+        assert_eq!(ctime(file.path()), before);
+
+        assert!(matches!(set_owner_if_changed("/nonexistent/file-owner", owner), Err(FileOwnerError::PathNotFound(_))));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_if_changed() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+
+        assert!(set_owner_if_changed(file.path(), "nobody").unwrap());
+        assert!(!set_owner_if_changed(file.path(), "nobody").unwrap());
+        assert!(set_group_if_changed(file.path(), "nogroup").unwrap());
+        assert!(!set_group_if_changed(file.path(), "nogroup").unwrap());
+        assert!(!set_owner_group_if_changed(file.path(), "nobody", "nogroup").unwrap());
+        assert!(set_owner_group_if_changed(file.path(), 0, "nogroup").unwrap());
+        assert_eq!(owner_group(file.path()).unwrap(), (Owner::from_uid(0), Group::from_name("nogroup").unwrap()));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_replace() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let nobody = Owner::from_name("nobody").unwrap();
+        let nogroup = Group::from_name("nogroup").unwrap();
+        let (owner, group) = owner_group(file.path()).unwrap();
+
+        assert_eq!(replace_owner(file.path(), nobody).unwrap(), owner);
+        assert_eq!(replace_owner(file.path(), owner).unwrap(), nobody);
+        assert_eq!(replace_group(file.path(), nogroup).unwrap(), group);
+        assert_eq!(replace_group(file.path(), group).unwrap(), nogroup);
+        assert_eq!(replace_owner_group(file.path(), nobody, nogroup).unwrap(), (owner, group));
+        assert_eq!(replace_owner_group(file.path(), owner, group).unwrap(), (nobody, nogroup));
+        assert_eq!(owner_group(file.path()).unwrap(), (owner, group));
+
+        assert!(matches!(replace_owner("/nonexistent/file-owner", nobody), Err(FileOwnerError::PathNotFound(_))));
+    }
+
+    #[test]
+    fn test_ord() {
+        let mut owners = [65534, 0, 1000, 321321].iter().copied().map(Owner::from_uid).collect::<Vec<_>>();
+        owners.sort();
+        assert_eq!(owners.iter().map(Owner::id).collect::<Vec<_>>(), vec![0, 1000, 65534, 321321]);
+
+        let mut groups = [65534, 0, 1000, 321321].iter().copied().map(Group::from_gid).collect::<Vec<_>>();
+        groups.sort();
+        assert_eq!(groups.iter().map(Group::id).collect::<Vec<_>>(), vec![0, 1000, 65534, 321321]);
+
+        let map: std::collections::BTreeMap<Owner, &str> = vec![(Owner::from_uid(2), "b"), (Owner::from_uid(1), "a")].into_iter().collect();
+        assert_eq!(map.values().copied().collect::<Vec<_>>(), vec!["a", "b"]);
+        assert!(Group::from_gid(1) < Group::from_gid(2));
+    }
+
+    #[test]
+    fn test_eq_id() {
+        assert!(Owner::from_uid(0) == 0);
+        assert!(0 == Owner::from_uid(0));
+        assert!(Owner::from_uid(1000) != 0);
+        assert!(0 != Owner::from_uid(1000));
+        assert_eq!(Owner::from_uid(321321), Owner::from_uid(321321));
+
+        assert!(Group::from_gid(0) == 0);
+        assert!(0 == Group::from_gid(0));
+        assert!(Group::from_gid(1000) != 0);
+        assert!(0 != Group::from_gid(1000));
+        assert_eq!(Group::from_gid(321321), Group::from_gid(321321));
+    }
+
+    #[test]
+    fn test_exists() {
+        assert!(Owner::from_uid(0).exists().unwrap());
+        assert!(Owner::from_name("nobody").unwrap().exists().unwrap());
+        assert!(!Owner::from_uid(321321).exists().unwrap());
+
+        assert!(Group::from_gid(0).exists().unwrap());
+        assert!(Group::from_name("nogroup").unwrap().exists().unwrap());
+        assert!(!Group::from_gid(321321).exists().unwrap());
+    }
+
+    #[test]
+    #[cfg(not(feature = "display-numeric"))]
+    fn test_display_alternate() {
+        assert_eq!(format!("{}", Owner::from_uid(0)), "root");
+        assert_eq!(format!("{:#}", Owner::from_uid(0)), "0");
+        assert_eq!(format!("{:#}", Owner::from_uid(321321)), "321321");
+
+        assert_eq!(format!("{}", Group::from_gid(0)), "root");
+        assert_eq!(format!("{:#}", Group::from_gid(0)), "0");
+        assert_eq!(format!("{:#}", Group::from_gid(321321)), "321321");
+    }
+
+    #[test]
+    #[cfg(feature = "display-numeric")]
+    fn test_display_numeric() {
+        assert_eq!(format!("{}", Owner::from_uid(0)), "0");
+        assert_eq!(format!("{:#}", Owner::from_uid(0)), "0");
+        assert_eq!(Owner::from_name("nobody").unwrap().to_string(), Owner::from_name("nobody").unwrap().id().to_string());
+        assert_eq!(format!("{}", Owner::from_uid(321321)), "321321");
+
+        assert_eq!(format!("{}", Group::from_gid(0)), "0");
+        assert_eq!(format!("{:#}", Group::from_gid(0)), "0");
+        assert_eq!(Group::from_name("nogroup").unwrap().to_string(), Group::from_name("nogroup").unwrap().id().to_string());
+        assert_eq!(format!("{}", Group::from_gid(321321)), "321321");
+    }
+
+    #[test]
+    fn test_name_or_id() {
+        assert_eq!(Owner::from_uid(0).name_or_id().unwrap(), "root");
+        assert_eq!(Owner::from_uid(321321).name_or_id().unwrap(), "321321");
+        assert_eq!(Group::from_gid(0).name_or_id().unwrap(), "root");
+        assert_eq!(Group::from_gid(321321).name_or_id().unwrap(), "321321");
+
+        assert_eq!(name_or_id(Ok(None), 321321).unwrap(), "321321");
+        assert!(matches!(name_or_id(Err(Errno::EIO.into()), 321321), Err(FileOwnerError::NixError(Errno::EIO))));
+    }
+
+    #[test]
+    fn test_nix_ids() {
+        let uid = Uid::from_raw(321321);
+        assert_eq!(Owner::from(uid).as_uid(), uid);
+        assert_eq!(Owner::from(uid), Owner::from_uid(321321));
+        assert_eq!(Owner::from(Uid::effective()).as_uid(), current_owner().as_uid());
+
+        let gid = Gid::from_raw(321321);
+        assert_eq!(Group::from(gid).as_gid(), gid);
+        assert_eq!(Group::from(gid), Group::from_gid(321321));
+    }
+
+    #[test]
+    fn test_from_metadata() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let meta = file.as_file().metadata().unwrap();
+
+        assert_eq!(Owner::from(&meta), owner(file.path()).unwrap());
+        assert_eq!(Group::from(&meta), group(file.path()).unwrap());
+        assert_eq!(Ownership::from_metadata(&meta), ownership(file.path()).unwrap());
+    }
+
+    #[test]
+    fn test_retry_eintr() {
+        let mut calls = 0;
+        assert_eq!(retry_eintr(|| { calls += 1; if calls < 3 { Err(Errno::EINTR) } else { Ok(calls) } }), Ok(3));
+
+        let mut calls = 0;
+        assert_eq!(retry_eintr(|| -> nix::Result<()> { calls += 1; Err(Errno::EINTR) }), Err(Errno::EINTR));
+        assert_eq!(calls, EINTR_ATTEMPTS);
+
+        let mut calls = 0;
+        assert_eq!(retry_eintr(|| -> nix::Result<()> { calls += 1; Err(Errno::EPERM) }), Err(Errno::EPERM));
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_errno() {
+        assert_eq!(chown_error(Path::new("/"), Errno::EPERM).errno(), Some(Errno::EPERM));
+        assert_eq!(chown_error(Path::new("/"), Errno::ENOENT).errno(), Some(Errno::ENOENT));
+        assert_eq!(chown_error(Path::new("/"), Errno::EROFS).errno(), Some(Errno::EROFS));
+        assert_eq!(FileOwnerError::from(io::Error::from_raw_os_error(Errno::EIO as i32)).errno(), Some(Errno::EIO));
+        assert_eq!(FileOwnerError::EntryError(PathBuf::from("/"), Box::new(Errno::EROFS.into())).errno(), Some(Errno::EROFS));
+        assert_eq!(FileOwnerError::UserNotFound("nonexistent-user".to_owned()).errno(), None);
+        assert_eq!(FileOwnerError::from(io::Error::other("custom")).errno(), None);
+
+        if current_owner() != 0 {
+            let file = tempfile::NamedTempFile::new().unwrap();
+            assert_eq!(set_owner(file.path(), 0).unwrap_err().errno(), Some(Errno::EPERM));
+        }
+    }
+
+    #[test]
+    fn test_into_io_error() {
+        let err = io::Error::from(chown_error(Path::new("/foo"), Errno::EPERM));
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        assert!(err.to_string().contains("\"/foo\""), "{}", err);
+
+        let err = io::Error::from(chown_error(Path::new("/foo"), Errno::ENOENT));
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(err.to_string().contains("\"/foo\""), "{}", err);
+
+        let err = io::Error::from(FileOwnerError::NixError(Errno::EACCES));
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        assert_eq!(err.raw_os_error(), Some(Errno::EACCES as i32));
+
+        let err = io::Error::from(FileOwnerError::IoError(io::Error::other("custom")));
+        assert_eq!(err.kind(), io::ErrorKind::Other);
+        assert_eq!(err.to_string(), "custom");
+
+        let err = io::Error::from(FileOwnerError::EntryError(PathBuf::from("/foo/bar"), Box::new(chown_error(Path::new("/foo/bar"), Errno::EPERM))));
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+
+        assert_eq!(io::Error::from(FileOwnerError::UserNotFound("nonexistent-user".to_owned())).kind(), io::ErrorKind::NotFound);
+
+        if current_owner() != 0 {
+            let file = tempfile::NamedTempFile::new().unwrap();
+            let err: io::Error = set_owner(file.path(), 0).unwrap_err().into();
+            assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        }
+    }
+
+    #[test]
+    fn test_ownership_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        fs::set_permissions(file.path(), fs::Permissions::from_mode(0o2754)).unwrap();
+
+        let (owner, group, mode) = file.path().ownership_mode().unwrap();
+        assert_eq!((owner, group), owner_group(file.path()).unwrap());
+        assert_eq!(mode, 0o2754);
+        assert_eq!(ownership_mode(file.path()).unwrap().2, 0o2754);
+    }
+
+    #[test]
+    fn test_is_root_system() {
+        assert!(Owner::from_uid(0).is_root());
+        assert!(Owner::from_uid(0).is_system());
+        assert!(!Owner::from_uid(500).is_root());
+        assert!(Owner::from_uid(500).is_system());
+        assert!(!Owner::from_uid(2000).is_root());
+        assert!(!Owner::from_uid(2000).is_system());
+        assert!(!Owner::from_uid(SYSTEM_UID_LIMIT).is_system());
+
+        assert!(Group::from_gid(0).is_root());
+        assert!(!Group::from_gid(500).is_root());
+        assert!(!Group::from_gid(2000).is_root());
+    }
+
+    #[test]
+    fn test_set_owner_canonical_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing").join("..").join("file");
+        assert!(matches!(set_owner_canonical(&path, 0), Err(FileOwnerError::PathNotFound(p)) if p == path));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_set_owner_canonical() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("real")).unwrap();
+        fs::write(dir.path().join("real").join("file"), b"").unwrap();
+        std::os::unix::fs::symlink("real", dir.path().join("link")).unwrap();
+
+        set_owner_canonical(dir.path().join("link").join("..").join("link").join("file"), "nobody").unwrap();
+        assert_eq!(owner(dir.path().join("real").join("file")).unwrap(), Owner::from_name("nobody").unwrap());
+        assert_eq!(owner_no_follow(dir.path().join("link")).unwrap(), owner(dir.path()).unwrap());
+    }
+
+    #[test]
+    fn test_special_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let fifo = dir.path().join("fifo");
+        nix::unistd::mkfifo(&fifo, nix::sys::stat::Mode::S_IRWXU).unwrap();
+        let socket = dir.path().join("socket");
+        let _listener = std::os::unix::net::UnixListener::bind(&socket).unwrap();
+
+        let expected = (current_owner(), owner_group(dir.path()).unwrap().1);
+        for path in &[&fifo, &socket] {
+            assert_eq!(owner_group(path).unwrap(), expected, "{:?}", path);
+            assert_eq!(owner_group_no_follow(path).unwrap(), expected, "{:?}", path);
+        }
+        assert_eq!(owner("/dev/null").unwrap(), Owner::from_uid(0));
+    }
+
+    #[test]
+    #[ignore]
+    #[cfg(target_os = "linux")]
+    fn test_set_special_files() {
+        use nix::sys::stat::{makedev, mknod, Mode, SFlag};
+
+        let dir = tempfile::tempdir().unwrap();
+        let fifo = dir.path().join("fifo");
+        nix::unistd::mkfifo(&fifo, Mode::S_IRWXU).unwrap();
+        let socket = dir.path().join("socket");
+        let _listener = std::os::unix::net::UnixListener::bind(&socket).unwrap();
+        let char_dev = dir.path().join("null");
+        mknod(&char_dev, SFlag::S_IFCHR, Mode::S_IRUSR, makedev(1, 3)).unwrap();
+        let block_dev = dir.path().join("loop");
+        mknod(&block_dev, SFlag::S_IFBLK, Mode::S_IRUSR, makedev(7, 0)).unwrap();
+
+        let expected = (Owner::from_name("nobody").unwrap(), Group::from_name("nogroup").unwrap());
+        for path in &[&fifo, &socket, &char_dev, &block_dev] {
+            set_owner_group(path, "nobody", "nogroup").unwrap();
+            assert_eq!(owner_group(path).unwrap(), expected, "{:?}", path);
+        }
+        assert_eq!(owner("/dev/null").unwrap(), Owner::from_uid(0));
+    }
+
+    #[test]
+    fn test_plan_set_owner() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let before = ownership(file.path()).unwrap();
+
+        let change = plan_set_owner(file.path(), 321321).unwrap();
+        assert_eq!(change.path, file.path());
+        assert_eq!(change.current, before);
+        assert_eq!(change.proposed, Ownership::new(Owner::from_uid(321321), before.group));
+        assert!(change.is_change());
+        assert_eq!(ownership(file.path()).unwrap(), before);
+
+        assert!(!plan_set_owner(file.path(), before.owner).unwrap().is_change());
+        assert!(matches!(plan_set_owner("/nonexistent/file-owner", 0), Err(FileOwnerError::PathNotFound(_))));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_plan_apply() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let change = plan_set_owner(file.path(), "nobody").unwrap();
+        change.apply().unwrap();
+        assert_eq!(ownership(file.path()).unwrap(), change.proposed);
+        assert!(!plan_set_owner(file.path(), "nobody").unwrap().is_change());
+    }
+
+    #[test]
+    #[ignore]
+    fn test_logged() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let before = ownership(file.path()).unwrap();
+        let nobody = Owner::from_name("nobody").unwrap();
+        let nogroup = Group::from_name("nogroup").unwrap();
+
+        let mut log = Vec::new();
+        set_owner_logged(file.path(), "nobody", |path, old, new| log.push((path.to_owned(), old, new))).unwrap();
+        assert_eq!(log, vec![(file.path().to_owned(), before.owner, nobody)]);
+
+        let mut log = Vec::new();
+        set_group_logged(file.path(), "nogroup", |path, old, new| log.push((path.to_owned(), old, new))).unwrap();
+        assert_eq!(log, vec![(file.path().to_owned(), before.group, nogroup)]);
+
+        let mut log = Vec::new();
+        set_owner_group_logged(file.path(), before.owner, before.group, |path, old, new| log.push((path.to_owned(), old, new))).unwrap();
+        assert_eq!(log, vec![(file.path().to_owned(), Ownership::new(nobody, nogroup), before)]);
+    }
+
+    #[test]
+    fn test_logged_failure() {
+        let mut calls = 0;
+        assert!(set_owner_logged("/nonexistent/file-owner", 0, |_, _, _| calls += 1).is_err());
+        assert!(set_group_logged("/nonexistent/file-owner", 0, |_, _, _| calls += 1).is_err());
+        assert!(set_owner_group_logged("/nonexistent/file-owner", 0, 0, |_, _, _| calls += 1).is_err());
+        assert_eq!(calls, 0);
+    }
+
+    #[test]
+    fn test_from_name_in() {
+        let dir = tempfile::tempdir().unwrap();
+        let passwd = dir.path().join("passwd");
+        fs::write(&passwd, "# image users\nroot:x:0:0:root:/root:/bin/sh\n\nbuilder:x:4242:4242:Image Builder:/home/builder:/bin/sh\n").unwrap();
+        let group = dir.path().join("group");
+        fs::write(&group, "root:x:0:\n# build group\nbuilders:x:4343:builder\n").unwrap();
+
+        assert_eq!(Owner::from_name_in("builder", &passwd).unwrap(), Owner::from_uid(4242));
+        assert_eq!(Owner::from_name_in("root", &passwd).unwrap(), Owner::from_uid(0));
+        assert!(matches!(Owner::from_name_in("nobody", &passwd), Err(FileOwnerError::UserNotFound(name)) if name == "nobody"));
+        assert!(matches!(Owner::from_name_in("#", &passwd), Err(FileOwnerError::UserNotFound(_))));
+
+        assert_eq!(Group::from_name_in("builders", &group).unwrap(), Group::from_gid(4343));
+        assert!(matches!(Group::from_name_in("builder", &group), Err(FileOwnerError::GroupNotFound(_))));
+
+        let missing = dir.path().join("missing");
+        assert!(matches!(Owner::from_name_in("root", &missing), Err(FileOwnerError::PathNotFound(path)) if path == missing));
+    }
+
+    #[test]
+    fn test_owner_all() {
+        let owners = Owner::all().unwrap();
+        assert!(owners.contains(&current_owner()));
+        assert!(owners.contains(&Owner::from_uid(0)));
+        assert!(owners.contains(&Owner::from_name("nobody").unwrap()));
+    }
+
+    #[test]
+    fn test_group_all() {
+        let groups = Group::all().unwrap();
+        assert!(groups.contains(&Group::from_gid(0)));
+        assert!(groups.contains(&Group::from_name("nogroup").unwrap()));
+    }
+
+    #[test]
+    fn test_set_owner_checked_unknown() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let before = owner(file.path()).unwrap();
+        assert!(matches!(set_owner_checked(file.path(), 321321), Err(FileOwnerError::UidNotFound(321321))));
+        assert_eq!(owner(file.path()).unwrap(), before);
+        set_owner_checked(file.path(), before).unwrap();
+    }
+
+    #[test]
+    #[ignore]
+    fn test_set_owner_checked() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let nobody = Owner::from_name("nobody").unwrap();
+        set_owner_checked(file.path(), nobody.id()).unwrap();
+        assert_eq!(owner(file.path()).unwrap(), nobody);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_owner_group_no_follow_matches_individual() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("target");
+        fs::write(&target, b"").unwrap();
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+        set_owner_group_no_follow(&link, "nobody", "nogroup").unwrap();
+
+        let (o, g) = link.owner_group_no_follow().unwrap();
+        assert_eq!(o, link.owner_no_follow().unwrap());
+        assert_eq!(g, link.group_no_follow().unwrap());
+        assert_eq!((o, g), (Owner::from_name("nobody").unwrap(), Group::from_name("nogroup").unwrap()));
+        assert_ne!((o, g), target.owner_group().unwrap());
+    }
+
+    #[test]
+    #[allow(clippy::useless_conversion)] // uid_t and gid_t are not u32 on all platforms
+    fn test_as_raw() {
+        for &id in &[0, 1000, 65534, 321321] {
+            assert_eq!(i64::from(Owner::from_uid(id).as_raw()), i64::from(id));
+            assert_eq!(i64::from(Group::from_gid(id).as_raw()), i64::from(id));
+        }
+        assert_eq!(current_owner().as_raw(), Uid::effective().as_raw());
+        assert_eq!(current_group().as_raw(), Gid::effective().as_raw());
+    }
+
+    #[test]
+    #[ignore]
+    fn test_copy_with_ownership() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        fs::write(&src, b"config").unwrap();
+        set_owner_group(&src, "nobody", "nogroup").unwrap();
+
+        let dst = dir.path().join("dst");
+        assert_eq!(copy_with_ownership(&src, &dst).unwrap(), 6);
+        assert_eq!(fs::read(&dst).unwrap(), b"config");
+        assert_eq!(owner_group(&dst).unwrap(), owner_group(&src).unwrap());
+
+        assert!(matches!(copy_with_ownership(dir.path().join("missing"), &dst), Err(FileOwnerError::PathNotFound(_))));
+    }
+
+    #[test]
+    fn test_copy_with_ownership_own() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        fs::write(&src, b"config").unwrap();
+        let dst = dir.path().join("dst");
+        copy_with_ownership(&src, &dst).unwrap();
+        assert_eq!(owner_group(&dst).unwrap(), owner_group(&src).unwrap());
+    }
+
+    #[test]
+    #[ignore]
+    fn test_swap_ownership() {
+        let a = tempfile::NamedTempFile::new().unwrap();
+        let b = tempfile::NamedTempFile::new().unwrap();
+        set_owner_group(b.path(), "nobody", "nogroup").unwrap();
+        let (before_a, before_b) = (ownership(a.path()).unwrap(), ownership(b.path()).unwrap());
+
+        assert_eq!(swap_ownership(a.path(), b.path()).unwrap(), (before_a, before_b));
+        assert_eq!(ownership(a.path()).unwrap(), before_b);
+        assert_eq!(ownership(b.path()).unwrap(), before_a);
+
+        assert!(matches!(swap_ownership(a.path(), "/nonexistent/file-owner"), Err(FileOwnerError::PathNotFound(_))));
+        assert_eq!(ownership(a.path()).unwrap(), before_b);
+    }
+
+    #[test]
+    fn test_can_change_owner() {
+        assert_eq!(can_change_owner(), geteuid().is_root() || has_cap_chown());
+        if geteuid().is_root() {
+            assert!(can_change_owner());
+        } else if !has_cap_chown() {
+            assert!(!can_change_owner());
+            let file = tempfile::NamedTempFile::new().unwrap();
+            assert!(matches!(set_owner(file.path(), 0), Err(FileOwnerError::PermissionDenied { .. })));
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn test_set_owner_then_group_rollback() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let original = ownership(file.path()).unwrap();
+        let nobody = Owner::from_name("nobody").unwrap();
+
+        let result = owner_then_group_rollback(file.path(), nobody, |path| {
+            assert_eq!(owner(path).unwrap(), nobody);
+            Err(FileOwnerError::PermissionDenied { path: path.to_owned() })
+        });
+        assert!(matches!(result, Err(FileOwnerError::RolledBack { original: o, source }) if o == original && matches!(*source, FileOwnerError::PermissionDenied { .. })));
+        assert_eq!(ownership(file.path()).unwrap(), original);
+
+        set_owner_then_group_rollback(file.path(), "nobody", "nogroup").unwrap();
+        assert_eq!(ownership(file.path()).unwrap(), Ownership::new(nobody, Group::from_name("nogroup").unwrap()));
+    }
+
+    #[test]
+    fn test_from_name_nul() {
+        assert!(matches!(Owner::from_name("nob\0ody"), Err(FileOwnerError::InvalidName { name, .. }) if name == "nob\0ody"));
+        assert!(matches!(Group::from_name("nog\0roup"), Err(FileOwnerError::InvalidName { .. })));
+        assert!(matches!("nob\0ody".parse::<Owner>(), Err(FileOwnerError::InvalidName { .. })));
+        assert_eq!(Owner::from_name("nob\0ody").unwrap_err().to_string(), "invalid name \"nob\\0ody\": name contains a NUL byte");
+    }
+
+    #[test]
+    fn test_owner_group_many() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = ["a", "b", "missing", "c"].iter().map(|name| dir.path().join(name)).collect::<Vec<_>>();
+        for path in paths.iter().filter(|path| !path.ends_with("missing")) {
+            fs::write(path, b"").unwrap();
+        }
+
+        let results = owner_group_many(&paths);
+        assert_eq!(results.len(), 4);
+        for (path, result) in paths.iter().zip(results) {
+            if path.ends_with("missing") {
+                assert!(matches!(result, Err(FileOwnerError::PathNotFound(p)) if &p == path));
+            } else {
+                assert_eq!(result.unwrap(), owner_group(path).unwrap());
+            }
+        }
+
+        let cache = UserCache::new();
+        let names = owner_group_many(&paths[..2]).into_iter().map(|result| cache.name_of(result.unwrap().0).unwrap()).collect::<Vec<_>>();
+        assert_eq!(names, vec![current_owner().name().unwrap(); 2]);
+    }
+
+    #[test]
+    fn test_group_contains() {
+        let nobody = Owner::from_name("nobody").unwrap();
+        assert!(nobody.primary_group().unwrap().contains(nobody).unwrap());
+        assert!(!Group::from_gid(0).contains(nobody).unwrap());
+        assert!(!Group::from_gid(321321).contains(nobody).unwrap());
+        assert!(matches!(Group::from_gid(0).contains(Owner::from_uid(321321)), Err(FileOwnerError::UidNotFound(321321))));
+
+        for group in Group::all().unwrap() {
+            for member in group.members().unwrap() {
+                if let Ok(owner) = Owner::from_name(&member) {
+                    assert!(group.contains(owner).unwrap(), "{} in {}", member, group);
+                    assert!(owner.groups().unwrap().contains(&group));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_with_path() {
+        let path = Path::new("/foo");
+        assert!(matches!(FileOwnerError::from(io::Error::from(io::ErrorKind::NotFound)).with_path(path), FileOwnerError::PathNotFound(p) if p == path));
+        assert!(matches!(FileOwnerError::from(io::Error::other("disk on fire")).with_path(path), FileOwnerError::Io { path: p, .. } if p == path));
+        assert!(matches!(FileOwnerError::from(Errno::ENOENT).with_path(path), FileOwnerError::PathNotFound(p) if p == path));
+        assert!(matches!(FileOwnerError::from(Errno::EIO).with_path(path), FileOwnerError::EntryError(p, err) if p == path && matches!(*err, FileOwnerError::NixError(Errno::EIO))));
+        assert!(matches!(FileOwnerError::UserNotFound("bar".to_owned()).with_path(path), FileOwnerError::EntryError(p, _) if p == path));
+
+        let err = FileOwnerError::PermissionDenied { path: PathBuf::from("/bar") }.with_path(path);
+        assert!(matches!(err, FileOwnerError::PermissionDenied { path: p } if p == Path::new("/bar")));
+        let err = FileOwnerError::PathNotFound(PathBuf::from("/bar")).with_path(path);
+        assert!(matches!(err, FileOwnerError::PathNotFound(p) if p == Path::new("/bar")));
+
+        let err: Box<dyn Error + Send + Sync> = FileOwnerError::from(Errno::EIO).with_path(path).into();
+        assert_eq!(err.to_string(), format!("error processing \"/foo\": *nix error: {}", Errno::EIO));
+        let source = err.source().unwrap().downcast_ref::<FileOwnerError>().unwrap();
+        assert!(matches!(source, FileOwnerError::NixError(Errno::EIO)));
+        assert_eq!(source.source().unwrap().downcast_ref::<Errno>(), Some(&Errno::EIO));
+    }
+
+    #[test]
+    fn test_ownership_diff() {
+        let ownership = Ownership::from_ids(1, 2);
+        assert_eq!(ownership.diff(&Ownership::from_ids(1, 2)), OwnershipDiff::Same);
+        assert_eq!(ownership.diff(&Ownership::from_ids(3, 2)), OwnershipDiff::Owner);
+        assert_eq!(ownership.diff(&Ownership::from_ids(1, 3)), OwnershipDiff::Group);
+        assert_eq!(ownership.diff(&Ownership::from_ids(3, 3)), OwnershipDiff::Both);
+        assert!(OwnershipDiff::Same.is_same());
+        assert!(!OwnershipDiff::Group.is_same());
+    }
+
+    #[test]
+    #[ignore]
+    fn test_diff_ownership() {
+        let a = tempfile::NamedTempFile::new().unwrap();
+        let b = tempfile::NamedTempFile::new().unwrap();
+        assert!(same_ownership(a.path(), b.path()).unwrap());
+        assert_eq!(diff_ownership(a.path(), b.path()).unwrap(), OwnershipDiff::Same);
+
+        set_owner(b.path(), "nobody").unwrap();
+        assert!(!same_ownership(a.path(), b.path()).unwrap());
+        assert_eq!(diff_ownership(a.path(), b.path()).unwrap(), OwnershipDiff::Owner);
+
+        set_ownership(b.path(), &ownership(a.path()).unwrap()).unwrap();
+        set_group(b.path(), "nogroup").unwrap();
+        assert_eq!(diff_ownership(a.path(), b.path()).unwrap(), OwnershipDiff::Group);
+
+        set_owner(b.path(), "nobody").unwrap();
+        assert_eq!(diff_ownership(a.path(), b.path()).unwrap(), OwnershipDiff::Both);
+
+        assert!(matches!(same_ownership(a.path(), "/nonexistent/file-owner"), Err(FileOwnerError::PathNotFound(_))));
+    }
+
+    #[test]
+    #[ignore]
+    #[cfg(target_os = "linux")]
+    fn test_set_owner_fd_path() {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let path_fd = fs::OpenOptions::new().read(true).custom_flags(nix::libc::O_PATH).open(file.path()).unwrap();
+        assert!(matches!(set_owner_fd(path_fd.as_raw_fd(), "nobody"), Err(FileOwnerError::NixError(Errno::EBADF))));
+
+        set_owner_fd_path(path_fd.as_raw_fd(), "nobody").unwrap();
+        assert_eq!(owner(file.path()).unwrap(), Owner::from_name("nobody").unwrap());
+        assert_eq!(group(file.path()).unwrap(), current_group());
+    }
+
+    #[test]
+    fn test_resolve() {
+        let nobody = Owner::from_name("nobody").unwrap();
+        assert_eq!(nobody.resolve().unwrap(), (nobody.id(), Some("nobody".to_owned())));
+        assert_eq!(Owner::from_uid(321321).resolve().unwrap(), (321321, None));
+
+        let nogroup = Group::from_name("nogroup").unwrap();
+        assert_eq!(nogroup.resolve().unwrap(), (nogroup.id(), Some("nogroup".to_owned())));
+        assert_eq!(Group::from_gid(321321).resolve().unwrap(), (321321, None));
+    }
+
+    #[test]
+    fn test_names_for_gid() {
+        let dir = tempfile::tempdir().unwrap();
+        let group = dir.path().join("group");
+        fs::write(&group, "root:x:0:\nusers:x:100:\n# alias\nstaff:x:100:alice\nwheel:x:10:\n").unwrap();
+
+        assert_eq!(Group::from_gid(100).names_for_gid_in(&group).unwrap(), vec!["users", "staff"]);
+        assert_eq!(Group::from_gid(0).names_for_gid_in(&group).unwrap(), vec!["root"]);
+        assert!(Group::from_gid(4343).names_for_gid_in(&group).unwrap().is_empty());
+        assert!(matches!(Group::from_gid(0).names_for_gid_in(&dir.path().join("missing")), Err(FileOwnerError::PathNotFound(_))));
+
+        let nogroup = Group::from_name("nogroup").unwrap();
+        assert!(nogroup.names_for_gid().unwrap().contains(&"nogroup".to_owned()));
+        assert_eq!(nogroup.names_for_gid().unwrap()[0], nogroup.name().unwrap().unwrap());
+        assert!(Group::from_gid(321321).names_for_gid().unwrap().is_empty());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_with_privileges() {
+        let ids = nix::unistd::getresuid().unwrap();
+        let can_elevate = ids.effective.is_root() || ids.real.is_root() || ids.saved.is_root();
+        let before = geteuid();
+
+        let result = with_privileges(|| Ok(geteuid()));
+        if can_elevate {
+            assert!(result.unwrap().is_root());
+        } else {
+            assert!(matches!(result, Err(FileOwnerError::NixError(Errno::EPERM))));
+        }
+        assert_eq!(geteuid(), before);
+
+        if can_elevate {
+            assert!(matches!(with_privileges(|| Err::<(), _>(FileOwnerError::Unsupported)), Err(FileOwnerError::Unsupported)));
+            assert!(std::panic::catch_unwind(|| with_privileges(|| -> Result<(), FileOwnerError> { panic!("in closure") })).is_err());
+            assert_eq!(geteuid(), before);
+        }
+    }
+
+    #[test]
+    fn test_is_owned_by() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let me = current_owner().name().unwrap().unwrap();
+        let my_group = current_group().name().unwrap().unwrap();
+
+        assert!(file.path().is_owned_by(me.as_str()).unwrap());
+        assert!(file.path().is_owned_by(current_owner()).unwrap());
+        assert!(!file.path().is_owned_by(321321).unwrap());
+        assert!(file.path().is_group(my_group.as_str()).unwrap());
+        assert!(!file.path().is_group(321321).unwrap());
+
+        if !current_owner().is_root() {
+            assert!(!file.path().is_owned_by("root").unwrap());
+        } else {
+            assert!(!file.path().is_owned_by("nobody").unwrap());
+            assert!(!file.path().is_group("nogroup").unwrap());
+        }
+        assert!(matches!(file.path().is_owned_by("no such user"), Err(FileOwnerError::UserNotFound(_))));
+        assert!(matches!(file.path().is_group("no such group"), Err(FileOwnerError::GroupNotFound(_))));
+        assert!(matches!(is_owned_by("/nonexistent/file-owner", 0), Err(FileOwnerError::PathNotFound(_))));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_inherit_parent_ownership() {
+        let dir = tempfile::tempdir().unwrap();
+        let parent = dir.path().join("parent");
+        fs::create_dir(&parent).unwrap();
+        set_owner_group(&parent, "nobody", "nogroup").unwrap();
+        let child = parent.join("child");
+        fs::write(&child, b"").unwrap();
+        let outside = dir.path().join("outside");
+        fs::write(&outside, b"").unwrap();
+        let link = parent.join("link");
+        std::os::unix::fs::symlink(&outside, &link).unwrap();
+
+        inherit_parent_ownership(&child).unwrap();
+        inherit_parent_ownership(&link).unwrap();
+
+        let expected = ownership(&parent).unwrap();
+        assert_eq!(ownership(&child).unwrap(), expected);
+        assert_eq!(Ownership::from(owner_group_no_follow(&link).unwrap()), expected);
+        assert_ne!(ownership(&outside).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_inherit_parent_ownership_no_parent() {
+        let err = inherit_parent_ownership("/").unwrap_err();
+        assert!(matches!(&err, FileOwnerError::Io { path, source } if path == Path::new("/") && source.kind() == io::ErrorKind::InvalidInput));
+        assert_eq!(err.to_string(), "I/O error on \"/\": path has no parent directory");
+        assert!(matches!(inherit_parent_ownership("/nonexistent/file-owner"), Err(FileOwnerError::PathNotFound(_))));
+    }
+
+    #[test]
+    fn test_non_utf8_path() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(OsStr::from_bytes(b"inv\xffalid\xc3("));
+        assert!(path.to_str().is_none());
+        fs::write(&path, b"").unwrap();
+
+        assert_eq!(owner(&path).unwrap(), current_owner());
+        assert_eq!(path.owner_group_no_follow().unwrap(), (current_owner(), current_group()));
+        set_owner(&path, current_owner()).unwrap();
+        path.set_owner_group(current_owner(), current_group()).unwrap();
+
+        let missing = dir.path().join(OsStr::from_bytes(b"missing\xff"));
+        assert!(matches!(set_owner(&missing, current_owner()), Err(FileOwnerError::PathNotFound(p)) if p.as_os_str().as_bytes() == missing.as_os_str().as_bytes()));
+        assert_eq!(find_not_owned_by(dir.path(), 321321).unwrap().last(), Some(&path));
+        assert_eq!(fs::read_dir(dir.path()).unwrap().next().unwrap().unwrap().file_name().as_bytes(), b"inv\xffalid\xc3(");
+    }
+
+    #[test]
+    #[ignore]
+    fn test_non_utf8_path_set_owner() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(OsStr::from_bytes(b"\xfe\xff"));
+        fs::write(&path, b"").unwrap();
+
+        set_owner_group(&path, "nobody", "nogroup").unwrap();
+        assert_eq!(ownership(&path).unwrap(), Ownership::from_names("nobody", "nogroup").unwrap());
+        set_owner_no_follow(&path, 0).unwrap();
+        assert_eq!(owner(&path).unwrap(), Owner::from_uid(0));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_apply_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in &["a", "b", "c"] {
+            fs::write(dir.path().join(name), b"").unwrap();
+        }
+        let before = ownership(dir.path().join("c")).unwrap();
+
+        let resolver = Resolver::new();
+        let manifest = [("a", "nobody:nogroup"), ("b", "nobody"), ("missing", "nobody:"), ("c", ":nogroup")].iter()
+            .map(|(name, spec)| (dir.path().join(name), resolver.resolve_spec(spec).unwrap()))
+            .collect::<Vec<_>>();
+
+        let errors = apply_manifest(manifest).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, dir.path().join("missing"));
+        assert!(matches!(errors[0].1, FileOwnerError::PathNotFound(_)));
+
+        let (nobody, nogroup) = (Owner::from_name("nobody").unwrap(), Group::from_name("nogroup").unwrap());
+        assert_eq!(ownership(dir.path().join("a")).unwrap(), Ownership::new(nobody, nogroup));
+        assert_eq!(owner(dir.path().join("b")).unwrap(), nobody);
+        assert_eq!(ownership(dir.path().join("c")).unwrap(), Ownership::new(before.owner, nogroup));
+
+        assert!(apply_manifest(vec![(dir.path().join("a"), OwnerGroupSpec::default())]).is_ok());
+    }
+
+    #[test]
+    fn test_name_eq() {
+        let nobody = Owner::from_name("nobody").unwrap();
+        assert!(nobody.name_eq(&nobody).unwrap());
+        assert!(!nobody.name_eq(&Owner::from_uid(0)).unwrap());
+        assert!(matches!(nobody.name_eq(&Owner::from_uid(321321)), Err(FileOwnerError::UidNotFound(321321))));
+
+        let nogroup = Group::from_name("nogroup").unwrap();
+        assert!(nogroup.name_eq(&nogroup).unwrap());
+        assert!(!nogroup.name_eq(&Group::from_gid(0)).unwrap());
+        assert!(matches!(Group::from_gid(321321).name_eq(&nogroup), Err(FileOwnerError::GidNotFound(321321))));
+    }
+}
@@ -0,0 +1,95 @@
+//! User and group database access.
+use crate::memdb::parse_entries;
+use crate::error::path_io_error;
+use crate::FileOwnerError;
+use nix::errno::Errno;
+use nix::libc;
+use nix::unistd::{Group as NixGroup, User};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Serializes use of the non-reentrant `getpwent` family within this crate.
+static USER_DB_LOCK: Mutex<()> = Mutex::new(());
+
+/// Serializes use of the non-reentrant `getgrent` family within this crate.
+static GROUP_DB_LOCK: Mutex<()> = Mutex::new(());
+
+/// Calls `f` with every entry of the user database.
+///
+/// Uses `setpwent`/`getpwent`/`endpwent` which are not reentrant; other code in the process using them concurrently will interfere.
+pub(crate) fn for_each_user(mut f: impl FnMut(User)) -> Result<(), FileOwnerError> {
+    let _lock = USER_DB_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+
+    unsafe { libc::setpwent() };
+    let result = loop {
+        Errno::clear();
+        let entry = unsafe { libc::getpwent() };
+        if entry.is_null() {
+            break match Errno::last() {
+                Errno::UnknownErrno | Errno::ENOENT => Ok(()),
+                err => Err(err.into()),
+            };
+        }
+        f(User::from(unsafe { &*entry }));
+    };
+    unsafe { libc::endpwent() };
+    result
+}
+
+/// Calls `f` with every entry of the group database.
+///
+/// Uses `setgrent`/`getgrent`/`endgrent` which are not reentrant; other code in the process using them concurrently will interfere.
+pub(crate) fn for_each_group(mut f: impl FnMut(NixGroup)) -> Result<(), FileOwnerError> {
+    let _lock = GROUP_DB_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+
+    unsafe { libc::setgrent() };
+    let result = loop {
+        Errno::clear();
+        let entry = unsafe { libc::getgrent() };
+        if entry.is_null() {
+            break match Errno::last() {
+                Errno::UnknownErrno | Errno::ENOENT => Ok(()),
+                err => Err(err.into()),
+            };
+        }
+        f(NixGroup::from(unsafe { &*entry }));
+    };
+    unsafe { libc::endgrent() };
+    result
+}
+
+/// Finds id of the entry with given name in a `passwd(5)` or `group(5)` formatted file.
+pub(crate) fn find_id_in_file(path: &Path, name: &str) -> Result<Option<u32>, FileOwnerError> {
+    let content = fs::read(path).map_err(|source| path_io_error(path, source))?;
+    let id = parse_entries(&String::from_utf8_lossy(&content)).find(|(entry, _id)| *entry == name).map(|(_name, id)| id);
+    Ok(id)
+}
+
+/// Finds all names with the given id in a `passwd(5)` or `group(5)` formatted file, in file order.
+pub(crate) fn find_names_in_file(path: &Path, id: u32) -> Result<Vec<String>, FileOwnerError> {
+    let content = fs::read(path).map_err(|source| path_io_error(path, source))?;
+    let names = parse_entries(&String::from_utf8_lossy(&content)).filter(|(_name, entry)| *entry == id).map(|(name, _id)| name.to_owned()).collect();
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_each_user() {
+        let mut names = Vec::new();
+        for_each_user(|user| names.push(user.name)).unwrap();
+        assert!(names.iter().any(|name| name == "root"));
+        assert!(names.iter().any(|name| name == "nobody"));
+    }
+
+    #[test]
+    fn test_for_each_group() {
+        let mut names = Vec::new();
+        for_each_group(|group| names.push(group.name)).unwrap();
+        assert!(names.iter().any(|name| name == "root"));
+        assert!(names.iter().any(|name| name == "nogroup"));
+    }
+}
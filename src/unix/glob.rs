@@ -0,0 +1,63 @@
+//! Ownership changes of paths matching glob patterns.
+use super::chown_ids;
+use crate::{FileOwnerError, Owner};
+use std::convert::TryInto;
+use std::path::PathBuf;
+
+/// Sets owner to all files matching the given glob pattern.
+///
+/// The owner is resolved once and applied to each match, following symlinks, with the outcome reported per path so one failing match does not abort the rest.
+/// Matches that could not be read while expanding the pattern are reported as [`FileOwnerError::Io`] for that path.
+/// A pattern that matches nothing is not an error and yields an empty list; an invalid pattern is reported as [`FileOwnerError::InvalidPattern`].
+#[allow(clippy::type_complexity)]
+pub fn set_owner_glob<E: Into<FileOwnerError>>(pattern: &str, owner: impl TryInto<Owner, Error = E>) -> Result<Vec<(PathBuf, Result<(), FileOwnerError>)>, FileOwnerError> {
+    let owner = owner.try_into().map_err(Into::into)?;
+    let paths = ::glob::glob(pattern).map_err(|err| FileOwnerError::InvalidPattern { pattern: pattern.to_owned(), message: err.to_string() })?;
+
+    Ok(paths.map(|entry| match entry {
+        Ok(path) => {
+            let result = chown_ids(&path, Some(owner.0), None, true);
+            (path, result)
+        }
+        Err(err) => {
+            let path = err.path().to_owned();
+            (path.clone(), Err(FileOwnerError::Io { path, source: err.into() }))
+        }
+    }).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::owner;
+    use std::fs;
+
+    #[test]
+    fn test_no_matches() {
+        let dir = tempfile::tempdir().unwrap();
+        let pattern = format!("{}/*.php", dir.path().display());
+        assert!(set_owner_glob(&pattern, 0).unwrap().is_empty());
+        assert!(matches!(set_owner_glob("[", 0), Err(FileOwnerError::InvalidPattern { .. })));
+        assert!(matches!(set_owner_glob(&pattern, "nonexistent-user"), Err(FileOwnerError::UserNotFound(_))));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_set_owner_glob() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("a.php"), b"").unwrap();
+        fs::write(dir.path().join("sub").join("b.php"), b"").unwrap();
+        fs::write(dir.path().join("c.txt"), b"").unwrap();
+
+        let results = set_owner_glob(&format!("{}/**/*.php", dir.path().display()), "nobody").unwrap();
+        let mut paths = results.iter().map(|(path, result)| { assert!(result.is_ok()); path.clone() }).collect::<Vec<_>>();
+        paths.sort();
+        assert_eq!(paths, vec![dir.path().join("a.php"), dir.path().join("sub").join("b.php")]);
+
+        let nobody = Owner::from_name("nobody").unwrap();
+        assert_eq!(owner(dir.path().join("a.php")).unwrap(), nobody);
+        assert_eq!(owner(dir.path().join("sub").join("b.php")).unwrap(), nobody);
+        assert_ne!(owner(dir.path().join("c.txt")).unwrap(), nobody);
+    }
+}
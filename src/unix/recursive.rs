@@ -0,0 +1,633 @@
+//! Recursive ownership changes over directory trees.
+use super::chown_ids;
+use crate::{FileOwnerError, Group, Owner, Ownership};
+use std::collections::HashSet;
+use std::convert::TryInto;
+use std::fs::{self, Metadata};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+#[cfg(feature = "walkdir")]
+use std::io;
+
+/// How symbolic links are handled by recursive operations.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum SymlinkPolicy {
+    /// Leaves symbolic links untouched.
+    Skip,
+    /// Changes ownership of the symbolic link itself (`lchown` semantics) and does not descend into it.
+    #[default]
+    NoFollow,
+    /// Changes ownership of the symbolic link target and descends into it if it is a directory.
+    ///
+    /// Directories already visited are not descended into again so symbolic link loops terminate.
+    Follow,
+}
+
+fn read_children(path: &Path) -> Result<Vec<PathBuf>, FileOwnerError> {
+    fs::read_dir(path)?.map(|entry| Ok(entry?.path())).collect()
+}
+
+/// Directory tree walk state.
+struct Walk {
+    policy: SymlinkPolicy,
+    max_depth: Option<usize>,
+    visited: HashSet<(u64, u64)>,
+}
+
+impl Walk {
+    fn new(policy: SymlinkPolicy) -> Walk {
+        Walk {
+            policy,
+            max_depth: None,
+            visited: HashSet::new(),
+        }
+    }
+
+    /// Limits the walk to entries at most `max_depth` levels below the root.
+    fn max_depth(mut self, max_depth: usize) -> Walk {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Gets metadata of the entry according to the symlink policy or `None` if it should be skipped.
+    fn metadata(&self, path: &Path) -> Result<Option<Metadata>, FileOwnerError> {
+        let meta = super::metadata(path, false)?;
+        if meta.file_type().is_symlink() {
+            match self.policy {
+                SymlinkPolicy::Skip => return Ok(None),
+                SymlinkPolicy::NoFollow => (),
+                SymlinkPolicy::Follow => return Ok(Some(super::metadata(path, true)?)),
+            }
+        }
+        Ok(Some(meta))
+    }
+
+    /// Checks if directory should be descended into; only followed symlinks can lead to the same directory twice.
+    fn first_visit(&mut self, meta: &Metadata) -> bool {
+        self.policy != SymlinkPolicy::Follow || self.visited.insert((meta.dev(), meta.ino()))
+    }
+
+    /// Walks the tree at `path` calling `f` for each entry, including the root.
+    ///
+    /// Errors are passed to `on_error` which decides if the walk should stop.
+    fn walk(&mut self, path: &Path, f: &mut impl FnMut(&Path, &Metadata) -> Result<(), FileOwnerError>, on_error: &mut impl FnMut(&Path, FileOwnerError) -> Result<(), FileOwnerError>) -> Result<(), FileOwnerError> {
+        self.walk_depth(path, 0, f, on_error)
+    }
+
+    fn walk_depth(&mut self, path: &Path, depth: usize, f: &mut impl FnMut(&Path, &Metadata) -> Result<(), FileOwnerError>, on_error: &mut impl FnMut(&Path, FileOwnerError) -> Result<(), FileOwnerError>) -> Result<(), FileOwnerError> {
+        let meta = match self.metadata(path) {
+            Ok(Some(meta)) => meta,
+            Ok(None) => return Ok(()),
+            Err(err) => return on_error(path, err),
+        };
+
+        if let Err(err) = f(path, &meta) {
+            on_error(path, err)?;
+        }
+
+        if meta.is_dir() && self.max_depth.is_none_or(|max_depth| depth < max_depth) && self.first_visit(&meta) {
+            let children = match read_children(path) {
+                Ok(children) => children,
+                Err(err) => return on_error(path, err),
+            };
+            for child in children {
+                self.walk_depth(&child, depth + 1, f, on_error)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn stop_on_error(path: &Path, err: FileOwnerError) -> Result<(), FileOwnerError> {
+    Err(FileOwnerError::EntryError(path.to_owned(), Box::new(err)))
+}
+
+/// Walks the whole tree collecting all errors instead of stopping on the first one.
+fn walk_collect(path: &Path, policy: SymlinkPolicy, f: &mut impl FnMut(&Path, &Metadata) -> Result<(), FileOwnerError>) -> Result<(), Vec<(PathBuf, FileOwnerError)>> {
+    let mut errors = Vec::new();
+    Walk::new(policy).walk(path, f, &mut |path, err| {
+        errors.push((path.to_owned(), err));
+        Ok(())
+    }).expect("collecting walk does not fail");
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Sets owner to all files in the directory tree at the given path, including the path itself.
+///
+/// Symbolic links are not followed during traversal; the ownership of the links themselves is changed (`lchown` semantics).
+/// Traversal stops on first error which is returned as [`FileOwnerError::EntryError`] carrying the failing path.
+pub fn set_owner_recursive<E: Into<FileOwnerError>>(path: impl AsRef<Path>, owner: impl TryInto<Owner, Error = E>) -> Result<(), FileOwnerError> {
+    set_owner_recursive_with(path, owner, SymlinkPolicy::NoFollow)
+}
+
+/// Sets owner to all files in the directory tree at the given path using given symlink policy.
+///
+/// The policy applies to the given path as well. Traversal stops on first error like with [`set_owner_recursive`].
+pub fn set_owner_recursive_with<E: Into<FileOwnerError>>(path: impl AsRef<Path>, owner: impl TryInto<Owner, Error = E>, policy: SymlinkPolicy) -> Result<(), FileOwnerError> {
+    let owner = owner.try_into().map_err(Into::into)?;
+    let follow = policy == SymlinkPolicy::Follow;
+    Walk::new(policy).walk(path.as_ref(), &mut |path, _meta| chown_ids(path, Some(owner.0), None, follow), &mut stop_on_error)
+}
+
+/// Sets owner and group to all files in the directory tree at the given path, including the path itself.
+///
+/// Both are applied with a single `lchown` call per entry. Uses the same symlink policy and error handling as [`set_owner_recursive`].
+pub fn set_owner_group_recursive<E1: Into<FileOwnerError>, E2: Into<FileOwnerError>>(path: impl AsRef<Path>, owner: impl TryInto<Owner, Error = E1>, group: impl TryInto<Group, Error = E2>) -> Result<(), FileOwnerError> {
+    let owner = owner.try_into().map_err(Into::into)?;
+    let group = group.try_into().map_err(Into::into)?;
+    Walk::new(SymlinkPolicy::NoFollow).walk(path.as_ref(), &mut |path, _meta| chown_ids(path, Some(owner.0), Some(group.0), false), &mut stop_on_error)
+}
+
+/// Sets owner to files in the directory tree at the given path up to given depth.
+///
+/// Depth 0 means just the given path, depth 1 the path and its direct children and so on, like `find -maxdepth`.
+/// Uses the same symlink policy and error handling as [`set_owner_recursive`].
+pub fn set_owner_recursive_depth<E: Into<FileOwnerError>>(path: impl AsRef<Path>, owner: impl TryInto<Owner, Error = E>, max_depth: usize) -> Result<(), FileOwnerError> {
+    let owner = owner.try_into().map_err(Into::into)?;
+    Walk::new(SymlinkPolicy::NoFollow).max_depth(max_depth).walk(path.as_ref(), &mut |path, _meta| chown_ids(path, Some(owner.0), None, false), &mut stop_on_error)
+}
+
+/// Sets owner to all files inside the directory at the given path, recursively, leaving the directory itself unchanged.
+///
+/// Useful when the directory is a mount point or otherwise needs to keep its own ownership.
+/// The policy applies to the entries inside the directory; with [`SymlinkPolicy::Follow`] the directory is left unchanged even when reached again through a symbolic link.
+/// Traversal stops on first error like with [`set_owner_recursive`].
+pub fn set_owner_contents<E: Into<FileOwnerError>>(path: impl AsRef<Path>, owner: impl TryInto<Owner, Error = E>, policy: SymlinkPolicy) -> Result<(), FileOwnerError> {
+    let owner = owner.try_into().map_err(Into::into)?;
+    let follow = policy == SymlinkPolicy::Follow;
+    let mut root = None;
+    Walk::new(policy).walk(path.as_ref(), &mut |path, meta| {
+        let id = (meta.dev(), meta.ino());
+        if *root.get_or_insert(id) == id {
+            return Ok(());
+        }
+        chown_ids(path, Some(owner.0), None, follow)
+    }, &mut stop_on_error)
+}
+
+/// Sets owner to all files in the directory tree at the given path that are not already owned by it.
+///
+/// Ownership is checked from the metadata already read by the walk, so entries with the right owner are not `chown`ed and keep their `ctime`.
+/// Returns the number of entries actually changed.
+/// Uses the same symlink policy and error handling as [`set_owner_recursive`].
+pub fn set_owner_recursive_if_changed<E: Into<FileOwnerError>>(path: impl AsRef<Path>, owner: impl TryInto<Owner, Error = E>) -> Result<usize, FileOwnerError> {
+    let owner = owner.try_into().map_err(Into::into)?;
+    let mut changed = 0;
+    Walk::new(SymlinkPolicy::NoFollow).walk(path.as_ref(), &mut |path, meta| {
+        if Owner::from(meta) != owner {
+            chown_ids(path, Some(owner.0), None, false)?;
+            changed += 1;
+        }
+        Ok(())
+    }, &mut stop_on_error)?;
+    Ok(changed)
+}
+
+/// Finds all files in the directory tree at the given path, including the path itself, not owned by the expected owner.
+///
+/// The expected owner is resolved once. Symbolic links are skipped ([`SymlinkPolicy::Skip`]): they are neither reported nor followed,
+/// since link ownership is rarely meaningful and following them could leave the tree.
+/// Traversal stops on first error like with [`set_owner_recursive`].
+pub fn find_not_owned_by<E: Into<FileOwnerError>>(dir: impl AsRef<Path>, expected: impl TryInto<Owner, Error = E>) -> Result<Vec<PathBuf>, FileOwnerError> {
+    let expected = expected.try_into().map_err(Into::into)?;
+    let mut found = Vec::new();
+    Walk::new(SymlinkPolicy::Skip).walk(dir.as_ref(), &mut |path, meta| {
+        if Owner::from(meta) != expected {
+            found.push(path.to_owned());
+        }
+        Ok(())
+    }, &mut stop_on_error)?;
+    Ok(found)
+}
+
+/// Sets owner to files in the directory tree at the given path for which the predicate returns `true`.
+///
+/// The predicate receives the entry metadata so current ownership can be checked without another `stat` call.
+/// Directories are descended into regardless of the predicate result.
+/// Uses the same symlink policy and error handling as [`set_owner_recursive`].
+pub fn set_owner_recursive_filter<E: Into<FileOwnerError>>(path: impl AsRef<Path>, owner: impl TryInto<Owner, Error = E>, mut predicate: impl FnMut(&Path, &Metadata) -> bool) -> Result<(), FileOwnerError> {
+    let owner = owner.try_into().map_err(Into::into)?;
+    Walk::new(SymlinkPolicy::NoFollow).walk(path.as_ref(), &mut |path, meta| {
+        if predicate(path, meta) {
+            chown_ids(path, Some(owner.0), None, false)
+        } else {
+            Ok(())
+        }
+    }, &mut stop_on_error)
+}
+
+/// Sets owner to all files in the directory tree at the given path using a thread pool.
+///
+/// The tree is listed first with the same symlink policy as [`set_owner_recursive`] and then the entries are changed in parallel.
+/// If any entry fails the error of the first failing entry in the listing order is returned.
+///
+/// Requires the `rayon` feature.
+#[cfg(feature = "rayon")]
+pub fn set_owner_recursive_parallel<E: Into<FileOwnerError>>(path: impl AsRef<Path>, owner: impl TryInto<Owner, Error = E>) -> Result<(), FileOwnerError> {
+    let owner = owner.try_into().map_err(Into::into)?;
+    let mut entries = Vec::new();
+    Walk::new(SymlinkPolicy::NoFollow).walk(path.as_ref(), &mut |path, _meta| {
+        entries.push(path.to_owned());
+        Ok(())
+    }, &mut stop_on_error)?;
+
+    match entries.par_iter().find_map_first(|path| chown_ids(path, Some(owner.0), None, false).err().map(|err| (path, err))) {
+        Some((path, err)) => stop_on_error(path, err),
+        None => Ok(()),
+    }
+}
+
+/// Sets owner to all files in the directory tree at the given path, continuing past errors.
+///
+/// Uses the same symlink policy as [`set_owner_recursive`] but visits every entry and returns all the per-path failures.
+/// If the owner cannot be resolved the error is reported against the given path.
+pub fn set_owner_recursive_collect<E: Into<FileOwnerError>>(path: impl AsRef<Path>, owner: impl TryInto<Owner, Error = E>) -> Result<(), Vec<(PathBuf, FileOwnerError)>> {
+    let path = path.as_ref();
+    let owner = owner.try_into().map_err(|err| vec![(path.to_owned(), err.into())])?;
+    walk_collect(path, SymlinkPolicy::NoFollow, &mut |path, _meta| chown_ids(path, Some(owner.0), None, false))
+}
+
+/// Applies owner and group of the `reference` file to all files in the directory tree at `target`, like `chown -R --reference`.
+///
+/// The reference is read once, following symlinks, and its ownership is applied to each entry with a single `chown` call.
+/// The policy applies to the target tree only. Traversal stops on first error like with [`set_owner_recursive`].
+pub fn chown_recursive_reference(reference: impl AsRef<Path>, target: impl AsRef<Path>, policy: SymlinkPolicy) -> Result<(), FileOwnerError> {
+    let Ownership { owner, group } = crate::ownership(reference)?;
+    let follow = policy == SymlinkPolicy::Follow;
+    Walk::new(policy).walk(target.as_ref(), &mut |path, _meta| chown_ids(path, Some(owner.0), Some(group.0), follow), &mut stop_on_error)
+}
+
+/// Sets owner to all files in the directory tree at the given path calling `on_entry` after each entry is changed.
+///
+/// The tree is traversed with `walkdir` and the callback can be used for progress reporting.
+/// Uses the same symlink policy and error handling as [`set_owner_recursive`].
+///
+/// Requires the `walkdir` feature.
+#[cfg(feature = "walkdir")]
+pub fn set_owner_recursive_with_progress<E: Into<FileOwnerError>>(path: impl AsRef<Path>, owner: impl TryInto<Owner, Error = E>, mut on_entry: impl FnMut(&Path)) -> Result<(), FileOwnerError> {
+    let owner = owner.try_into().map_err(Into::into)?;
+    let root = path.as_ref();
+    for entry in walkdir::WalkDir::new(root) {
+        let entry = entry.map_err(|err| {
+            let path = err.path().unwrap_or(root).to_owned();
+            let err = crate::error::path_io_error(&path, io::Error::from(err));
+            FileOwnerError::EntryError(path, Box::new(err))
+        })?;
+        if let Err(err) = chown_ids(entry.path(), Some(owner.0), None, false) {
+            return stop_on_error(entry.path(), err);
+        }
+        on_entry(entry.path());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{owner, owner_no_follow};
+
+    #[test]
+    fn test_set_owner_recursive_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing");
+
+        match set_owner_recursive(&missing, 0) {
+            Err(FileOwnerError::EntryError(path, err)) => {
+                assert_eq!(path, missing);
+                assert!(matches!(*err, FileOwnerError::PathNotFound(_)));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_walk_collect() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("a/fail")).unwrap();
+        fs::write(dir.path().join("a/fail/file"), b"").unwrap();
+        fs::write(dir.path().join("a/fail.txt"), b"").unwrap();
+        fs::write(dir.path().join("a/file"), b"").unwrap();
+
+        let mut visited = Vec::new();
+        let errors = walk_collect(dir.path(), SymlinkPolicy::NoFollow, &mut |path, _meta| {
+            visited.push(path.to_owned());
+            if path.file_name().unwrap().to_str().unwrap().starts_with("fail") {
+                Err(FileOwnerError::UserNotFound("test".to_owned()))
+            } else {
+                Ok(())
+            }
+        }).unwrap_err();
+
+        assert_eq!(visited.len(), 6);
+        let mut failed = errors.into_iter().map(|(path, _err)| path).collect::<Vec<_>>();
+        failed.sort();
+        assert_eq!(failed, vec![dir.path().join("a/fail"), dir.path().join("a/fail.txt")]);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_set_owner_recursive_collect() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("a")).unwrap();
+        fs::write(dir.path().join("a/file"), b"").unwrap();
+
+        set_owner_recursive_collect(dir.path(), "nobody").unwrap();
+        assert_eq!(owner(dir.path().join("a/file")).unwrap().name().unwrap().as_deref(), Some("nobody"));
+
+        let errors = set_owner_recursive_collect(dir.path().join("missing"), "nobody").unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, dir.path().join("missing"));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_set_owner_recursive() {
+        let outside = tempfile::NamedTempFile::new().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        fs::write(dir.path().join("a/file"), b"").unwrap();
+        fs::write(dir.path().join("a/b/file"), b"").unwrap();
+        std::os::unix::fs::symlink(outside.path(), dir.path().join("a/link")).unwrap();
+
+        set_owner_recursive(dir.path(), "nobody").unwrap();
+
+        let nobody = Owner::from_name("nobody").unwrap();
+        for path in &["", "a", "a/b", "a/file", "a/b/file", "a/link"] {
+            assert_eq!(owner_no_follow(dir.path().join(path)).unwrap(), nobody, "{}", path);
+        }
+        assert_eq!(owner(outside.path()).unwrap().id(), 0);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_set_owner_recursive_with() {
+        let nobody = Owner::from_name("nobody").unwrap();
+        let root = Owner::from_uid(0);
+
+        for &policy in &[SymlinkPolicy::Skip, SymlinkPolicy::NoFollow, SymlinkPolicy::Follow] {
+            let outside = tempfile::tempdir().unwrap();
+            fs::write(outside.path().join("file"), b"").unwrap();
+            let dir = tempfile::tempdir().unwrap();
+            fs::create_dir(dir.path().join("a")).unwrap();
+            fs::write(dir.path().join("a/file"), b"").unwrap();
+            std::os::unix::fs::symlink(outside.path(), dir.path().join("a/outside")).unwrap();
+            std::os::unix::fs::symlink(dir.path(), dir.path().join("a/loop")).unwrap();
+
+            set_owner_recursive_with(dir.path(), "nobody", policy).unwrap();
+
+            assert_eq!(owner(dir.path().join("a/file")).unwrap(), nobody);
+            let link_owner = owner_no_follow(dir.path().join("a/outside")).unwrap();
+            let outside_owner = owner(outside.path().join("file")).unwrap();
+            match policy {
+                SymlinkPolicy::Skip => assert_eq!((link_owner, outside_owner), (root, root)),
+                SymlinkPolicy::NoFollow => assert_eq!((link_owner, outside_owner), (nobody, root)),
+                SymlinkPolicy::Follow => assert_eq!((link_owner, outside_owner), (root, nobody)),
+            }
+        }
+    }
+
+    #[test]
+    #[ignore]
+    #[cfg(feature = "rayon")]
+    fn test_set_owner_recursive_parallel() {
+        let dir = tempfile::tempdir().unwrap();
+        for d in 0..20 {
+            let sub = dir.path().join(d.to_string());
+            fs::create_dir(&sub).unwrap();
+            for f in 0..200 {
+                fs::write(sub.join(f.to_string()), b"").unwrap();
+            }
+        }
+
+        set_owner_recursive_parallel(dir.path(), "nobody").unwrap();
+
+        let nobody = Owner::from_name("nobody").unwrap();
+        assert_eq!(owner(dir.path()).unwrap(), nobody);
+        for d in 0..20 {
+            for f in 0..200 {
+                assert_eq!(owner(dir.path().join(d.to_string()).join(f.to_string())).unwrap(), nobody);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "walkdir")]
+    fn test_set_owner_recursive_with_progress_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing");
+        let mut calls = 0;
+
+        match set_owner_recursive_with_progress(&missing, 0, |_path| calls += 1) {
+            Err(FileOwnerError::EntryError(path, err)) => {
+                assert_eq!(path, missing);
+                assert!(matches!(*err, FileOwnerError::PathNotFound(_)));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(calls, 0);
+    }
+
+    #[test]
+    #[ignore]
+    #[cfg(feature = "walkdir")]
+    fn test_set_owner_recursive_with_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        fs::write(dir.path().join("a/file"), b"").unwrap();
+        fs::write(dir.path().join("a/b/file"), b"").unwrap();
+        std::os::unix::fs::symlink("file", dir.path().join("a/link")).unwrap();
+
+        let mut seen = Vec::new();
+        set_owner_recursive_with_progress(dir.path(), "nobody", |path| seen.push(path.to_owned())).unwrap();
+
+        seen.sort();
+        let mut expected = ["", "a", "a/b", "a/file", "a/b/file", "a/link"].iter().map(|path| dir.path().join(path)).collect::<Vec<_>>();
+        expected.sort();
+        assert_eq!(seen, expected);
+
+        let nobody = Owner::from_name("nobody").unwrap();
+        for path in &expected {
+            assert_eq!(owner_no_follow(path).unwrap(), nobody, "{:?}", path);
+        }
+    }
+
+    #[test]
+    fn test_walk_max_depth() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("a/b/c")).unwrap();
+
+        for max_depth in 0..4 {
+            let mut visited = 0;
+            Walk::new(SymlinkPolicy::NoFollow).max_depth(max_depth).walk(dir.path(), &mut |_path, _meta| {
+                visited += 1;
+                Ok(())
+            }, &mut stop_on_error).unwrap();
+            assert_eq!(visited, max_depth + 1);
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn test_set_owner_recursive_depth() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        fs::write(dir.path().join("file"), b"").unwrap();
+        fs::write(dir.path().join("a/b/file"), b"").unwrap();
+
+        set_owner_recursive_depth(dir.path(), "nobody", 1).unwrap();
+
+        let nobody = Owner::from_name("nobody").unwrap();
+        assert_eq!(owner(dir.path()).unwrap(), nobody);
+        assert_eq!(owner(dir.path().join("file")).unwrap(), nobody);
+        assert_eq!(owner(dir.path().join("a")).unwrap(), nobody);
+        assert_eq!(owner(dir.path().join("a/b")).unwrap().id(), 0);
+        assert_eq!(owner(dir.path().join("a/b/file")).unwrap().id(), 0);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_set_owner_recursive_filter() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("root"), b"").unwrap();
+        fs::write(dir.path().join("other"), b"").unwrap();
+        crate::set_owner(dir.path().join("other"), 1).unwrap();
+
+        set_owner_recursive_filter(dir.path(), "nobody", |_path, meta| meta.uid() == 0).unwrap();
+
+        assert_eq!(owner(dir.path()).unwrap().name().unwrap().as_deref(), Some("nobody"));
+        assert_eq!(owner(dir.path().join("root")).unwrap().name().unwrap().as_deref(), Some("nobody"));
+        assert_eq!(owner(dir.path().join("other")).unwrap().id(), 1);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_set_owner_group_recursive() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("a")).unwrap();
+        fs::write(dir.path().join("a/file"), b"").unwrap();
+        std::os::unix::fs::symlink("file", dir.path().join("a/link")).unwrap();
+
+        set_owner_group_recursive(dir.path(), "nobody", "nogroup").unwrap();
+
+        let expected = (Owner::from_name("nobody").unwrap(), Group::from_name("nogroup").unwrap());
+        for path in &["", "a", "a/file", "a/link"] {
+            assert_eq!(crate::owner_group_no_follow(dir.path().join(path)).unwrap(), expected, "{}", path);
+        }
+
+        match set_owner_group_recursive(dir.path().join("missing"), "nobody", "nogroup") {
+            Err(FileOwnerError::EntryError(path, _)) => assert_eq!(path, dir.path().join("missing")),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn test_chown_recursive_reference() {
+        let reference = tempfile::NamedTempFile::new().unwrap();
+        crate::set_owner_group(reference.path(), "nobody", "nogroup").unwrap();
+        let outside = tempfile::NamedTempFile::new().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        fs::write(dir.path().join("a/file"), b"").unwrap();
+        fs::write(dir.path().join("a/b/file"), b"").unwrap();
+        std::os::unix::fs::symlink(outside.path(), dir.path().join("a/link")).unwrap();
+
+        chown_recursive_reference(reference.path(), dir.path(), SymlinkPolicy::Skip).unwrap();
+
+        let expected = crate::ownership(reference.path()).unwrap();
+        for path in &["", "a", "a/b", "a/file", "a/b/file"] {
+            assert_eq!(Ownership::from(crate::owner_group_no_follow(dir.path().join(path)).unwrap()), expected, "{}", path);
+        }
+        assert_ne!(Ownership::from(crate::owner_group_no_follow(dir.path().join("a/link")).unwrap()), expected);
+        assert_ne!(crate::ownership(outside.path()).unwrap(), expected);
+
+        assert!(matches!(chown_recursive_reference(dir.path().join("missing"), dir.path(), SymlinkPolicy::Skip), Err(FileOwnerError::PathNotFound(_))));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_set_owner_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        fs::write(dir.path().join("a/file"), b"").unwrap();
+        fs::write(dir.path().join("a/b/file"), b"").unwrap();
+        std::os::unix::fs::symlink(dir.path(), dir.path().join("a/root")).unwrap();
+        let before = owner(dir.path()).unwrap();
+
+        set_owner_contents(dir.path(), "nobody", SymlinkPolicy::Follow).unwrap();
+
+        let nobody = Owner::from_name("nobody").unwrap();
+        assert_ne!(before, nobody);
+        assert_eq!(owner(dir.path()).unwrap(), before);
+        for path in &["a", "a/b", "a/file", "a/b/file"] {
+            assert_eq!(owner_no_follow(dir.path().join(path)).unwrap(), nobody, "{}", path);
+        }
+        assert_ne!(owner_no_follow(dir.path().join("a/root")).unwrap(), nobody);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_set_owner_recursive_if_changed() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        for path in &["a/file", "a/b/file", "a/b/other"] {
+            fs::write(dir.path().join(path), b"").unwrap();
+        }
+        crate::set_owner(dir.path(), "nobody").unwrap();
+        crate::set_owner(dir.path().join("a/b"), "nobody").unwrap();
+        crate::set_owner(dir.path().join("a/b/file"), "nobody").unwrap();
+        let ctime = |path: &str| {
+            let meta = fs::metadata(dir.path().join(path)).unwrap();
+            (meta.ctime(), meta.ctime_nsec())
+        };
+        let before = ["", "a/b", "a/b/file"].iter().map(|path| ctime(path)).collect::<Vec<_>>();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+
+        assert_eq!(set_owner_recursive_if_changed(dir.path(), "nobody").unwrap(), 3);
+
+        let nobody = Owner::from_name("nobody").unwrap();
+        for path in &["", "a", "a/b", "a/file", "a/b/file", "a/b/other"] {
+            assert_eq!(owner(dir.path().join(path)).unwrap(), nobody, "{}", path);
+        }
+        assert_eq!(["", "a/b", "a/b/file"].iter().map(|path| ctime(path)).collect::<Vec<_>>(), before);
+        assert_eq!(set_owner_recursive_if_changed(dir.path(), "nobody").unwrap(), 0);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_find_not_owned_by() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        for path in &["a/file", "a/b/file", "a/b/other"] {
+            fs::write(dir.path().join(path), b"").unwrap();
+        }
+        std::os::unix::fs::symlink("/", dir.path().join("a/link")).unwrap();
+        crate::set_owner(dir.path().join("a/b/file"), "nobody").unwrap();
+        crate::set_owner(dir.path().join("a/b"), "nobody").unwrap();
+
+        let mut found = find_not_owned_by(dir.path(), crate::current_owner()).unwrap();
+        found.sort();
+        assert_eq!(found, vec![dir.path().join("a/b"), dir.path().join("a/b/file")]);
+
+        let found = find_not_owned_by(dir.path(), "nobody").unwrap();
+        assert_eq!(found.len(), 4);
+        assert!(!found.contains(&dir.path().join("a/link")));
+    }
+
+    #[test]
+    fn test_find_not_owned_by_own() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("file"), b"").unwrap();
+        assert!(find_not_owned_by(dir.path(), crate::current_owner()).unwrap().is_empty());
+        assert_eq!(find_not_owned_by(dir.path(), 321321).unwrap().len(), 2);
+        assert!(matches!(find_not_owned_by(dir.path().join("missing"), 0), Err(FileOwnerError::EntryError(..))));
+    }
+}
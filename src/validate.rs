@@ -0,0 +1,81 @@
+//! Syntactic validation of user and group names.
+use crate::FileOwnerError;
+
+/// Checks that a user name is acceptable for lookup in the user database.
+///
+/// A valid name is not empty, does not start with `-` and is not made of ASCII digits only, since such names would be taken as UIDs by this crate.
+/// It must not contain NUL bytes, which cannot be passed to the C library, nor `/`, `:`, `,`, whitespace or control characters, which have special meaning in `passwd(5)` and `group(5)` files.
+/// Other characters, including non-ASCII ones and `.`, `@`, `$` and `\` used by some directory services, are accepted.
+/// A valid name does not necessarily exist.
+pub fn validate_user_name(name: &str) -> Result<(), FileOwnerError> {
+    validate_name(name)
+}
+
+/// Checks that a group name is acceptable for lookup in the group database.
+///
+/// Applies the same policy as [`validate_user_name`].
+pub fn validate_group_name(name: &str) -> Result<(), FileOwnerError> {
+    validate_name(name)
+}
+
+const NUL_REASON: &str = "name contains a NUL byte";
+
+/// Rejects names with NUL bytes, which cannot be passed to the C library.
+#[cfg_attr(not(unix), allow(dead_code))]
+pub(crate) fn check_no_nul(name: &str) -> Result<(), FileOwnerError> {
+    match name.contains('\0') {
+        true => Err(FileOwnerError::InvalidName { name: name.to_owned(), reason: NUL_REASON }),
+        false => Ok(()),
+    }
+}
+
+fn validate_name(name: &str) -> Result<(), FileOwnerError> {
+    let reason = if name.is_empty() {
+        "name is empty"
+    } else if name.contains('\0') {
+        NUL_REASON
+    } else if name.starts_with('-') {
+        "name starts with '-'"
+    } else if name.bytes().all(|b| b.is_ascii_digit()) {
+        "name is numeric"
+    } else if name.contains(['/', ':', ',']) {
+        "name contains '/', ':' or ','"
+    } else if name.contains(|c: char| c.is_whitespace() || c.is_control()) {
+        "name contains whitespace or control characters"
+    } else {
+        return Ok(());
+    };
+    Err(FileOwnerError::InvalidName { name: name.to_owned(), reason })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_names() {
+        for name in ["nobody", "nogroup", "_apt", "systemd-network", "user.name", "a1", "1a", "host$", "DOMAIN\\user", "user@example.com", "zoë"] {
+            assert!(validate_user_name(name).is_ok(), "{:?}", name);
+            assert!(validate_group_name(name).is_ok(), "{:?}", name);
+        }
+    }
+
+    #[test]
+    fn test_invalid_names() {
+        for (name, reason) in [
+            ("", "name is empty"),
+            ("nob\0ody", "name contains a NUL byte"),
+            ("-nobody", "name starts with '-'"),
+            ("1000", "name is numeric"),
+            ("a/b", "name contains '/', ':' or ','"),
+            ("nobody:x", "name contains '/', ':' or ','"),
+            ("a,b", "name contains '/', ':' or ','"),
+            ("no body", "name contains whitespace or control characters"),
+            ("nobody\n", "name contains whitespace or control characters"),
+        ] {
+            assert!(matches!(validate_user_name(name), Err(FileOwnerError::InvalidName { name: n, reason: r }) if n == name && r == reason), "{:?}", name);
+            assert!(matches!(validate_group_name(name), Err(FileOwnerError::InvalidName { .. })), "{:?}", name);
+        }
+        assert_eq!(validate_user_name("").unwrap_err().to_string(), "invalid name \"\": name is empty");
+    }
+}